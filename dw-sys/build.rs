@@ -1,8 +1,17 @@
 use pkg_config;
 
 fn main() {
-    pkg_config::probe_library("libdw").unwrap();
+    let library = pkg_config::probe_library("libdw").unwrap();
 
+    // elfutils versions are of the form 0.186
+    let mut it = library.version.split('.');
+    let major = it.next().unwrap().parse::<u32>().unwrap();
+    let minor = it.next().unwrap().parse::<u32>().unwrap();
+    if major == 0 && minor < 188 {
+        println!("cargo:rustc-cfg=pre188");
+    }
+
+    println!("cargo:version={}", library.version);
     let includedir = pkg_config::get_variable("libdw", "includedir").unwrap();
     println!("cargo:includedir={}", includedir);
 }
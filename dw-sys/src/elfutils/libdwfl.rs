@@ -482,4 +482,8 @@ extern "C" {
         pc: *mut Dwarf_Addr,
         isactivation: *mut bool,
     ) -> bool;
+
+    // exported since elfutils 0.188
+    #[cfg(not(pre188))]
+    pub fn dwfl_frame_reg(state: *mut Dwfl_Frame, regno: c_uint, val: *mut Dwarf_Word) -> c_int;
 }
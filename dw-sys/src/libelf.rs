@@ -64,6 +64,8 @@ c_enum! {
     }
 }
 
+pub const EV_CURRENT: c_uint = 1;
+
 pub const ELF_F_DIRTY: c_uint = 0x1;
 pub const ELF_F_LAYOUT: c_uint = 0x4;
 pub const ELF_F_PERMISSIVE: c_uint = 0x8;
@@ -6,6 +6,14 @@ fn main() {
     let includedir = env::var_os("DEP_DW_INCLUDEDIR").unwrap();
     cfg.include(includedir);
 
+    let version = env::var("DEP_DW_VERSION").unwrap();
+    let mut it = version.split('.');
+    let major = it.next().unwrap().parse::<u32>().unwrap();
+    let minor = it.next().unwrap().parse::<u32>().unwrap();
+    if major == 0 && minor < 188 {
+        cfg.cfg("pre188", None);
+    }
+
     cfg.header("elfutils/libdwfl.h")
         .header("elfutils/libdwelf.h")
         .header("dwarf.h")
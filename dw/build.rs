@@ -0,0 +1,11 @@
+use std::env;
+
+fn main() {
+    let version = env::var("DEP_DW_VERSION").unwrap();
+    let mut it = version.split('.');
+    let major = it.next().unwrap().parse::<u32>().unwrap();
+    let minor = it.next().unwrap().parse::<u32>().unwrap();
+    if major == 0 && minor < 188 {
+        println!("cargo:rustc-cfg=pre188");
+    }
+}
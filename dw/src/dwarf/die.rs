@@ -0,0 +1,173 @@
+use libc::{c_uint, c_void};
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::slice;
+
+use crate::dwarf::{cvt, Error};
+
+/// The tag of a debugging information entry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Tag(i32);
+
+impl Tag {
+    /// A compilation unit.
+    pub const COMPILE_UNIT: Tag = Tag(dw_sys::DW_TAG_compile_unit);
+
+    /// A lexical block.
+    pub const LEXICAL_BLOCK: Tag = Tag(dw_sys::DW_TAG_lexical_block);
+
+    /// A subprogram (i.e. a function).
+    pub const SUBPROGRAM: Tag = Tag(dw_sys::DW_TAG_subprogram);
+
+    /// An inlined instance of a subprogram.
+    pub const INLINED_SUBROUTINE: Tag = Tag(dw_sys::DW_TAG_inlined_subroutine);
+
+    /// Returns the raw value of the tag.
+    pub fn as_raw(&self) -> i32 {
+        self.0
+    }
+}
+
+/// A debugging information entry.
+#[derive(Copy, Clone)]
+pub struct Die<'a>(dw_sys::Dwarf_Die, PhantomData<&'a ()>);
+
+impl<'a> Die<'a> {
+    pub(crate) unsafe fn from_raw(die: dw_sys::Dwarf_Die) -> Die<'a> {
+        Die(die, PhantomData)
+    }
+
+    fn as_ptr(&self) -> *mut dw_sys::Dwarf_Die {
+        &self.0 as *const _ as *mut _
+    }
+
+    /// Returns the entry's tag.
+    pub fn tag(&self) -> Tag {
+        unsafe { Tag(dw_sys::dwarf_tag(self.as_ptr())) }
+    }
+
+    /// Returns the entry's name, if it has one.
+    ///
+    /// Entries such as inlined subroutines which refer to an abstract origin will return the name of that origin.
+    pub fn name(&self) -> Option<&'a CStr> {
+        unsafe {
+            let ptr = dw_sys::dwarf_diename(self.as_ptr());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ptr))
+            }
+        }
+    }
+
+    /// Returns the entry's linkage (i.e. mangled) name, if it has one.
+    pub fn linkage_name(&self) -> Option<&'a CStr> {
+        self.string_attr(dw_sys::DW_AT_linkage_name)
+            .or_else(|| self.string_attr(dw_sys::DW_AT_MIPS_linkage_name))
+    }
+
+    /// Returns the path of the source file containing the call site of an inlined subroutine.
+    pub fn call_file(&self) -> Option<&'a CStr> {
+        unsafe {
+            let idx = self.udata_attr(dw_sys::DW_AT_call_file)?;
+
+            let mut cu = MaybeUninit::uninit();
+            if dw_sys::dwarf_diecu(
+                self.as_ptr(),
+                cu.as_mut_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            )
+            .is_null()
+            {
+                return None;
+            }
+
+            let mut files = ptr::null_mut();
+            let mut nfiles = 0;
+            cvt(dw_sys::dwarf_getsrcfiles(
+                cu.as_mut_ptr(),
+                &mut files,
+                &mut nfiles,
+            ))
+            .ok()?;
+            if idx >= nfiles as u64 {
+                return None;
+            }
+
+            let ptr = dw_sys::dwarf_filesrc(files, idx as usize, ptr::null_mut(), ptr::null_mut());
+            if ptr.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ptr))
+            }
+        }
+    }
+
+    /// Returns the line number of the call site of an inlined subroutine.
+    pub fn call_line(&self) -> Option<u64> {
+        self.udata_attr(dw_sys::DW_AT_call_line)
+    }
+
+    /// Returns the column number of the call site of an inlined subroutine.
+    pub fn call_column(&self) -> Option<u64> {
+        self.udata_attr(dw_sys::DW_AT_call_column)
+    }
+
+    /// Returns the entries whose address ranges contain the specified address, starting with the innermost.
+    ///
+    /// This must be called on a compilation unit entry, and the address must be relative to the module's bias.
+    pub fn scopes(&self, pc: u64) -> Result<Vec<Die<'a>>, Error> {
+        unsafe {
+            let mut scopes = ptr::null_mut();
+            let n = dw_sys::dwarf_getscopes(self.as_ptr(), pc, &mut scopes);
+            if n < 0 {
+                return Err(Error::new());
+            }
+            if n == 0 {
+                return Ok(vec![]);
+            }
+
+            let dies = slice::from_raw_parts(scopes, n as usize)
+                .iter()
+                .map(|die| Die::from_raw(*die))
+                .collect();
+            libc::free(scopes as *mut c_void);
+
+            Ok(dies)
+        }
+    }
+
+    fn string_attr(&self, name: c_uint) -> Option<&'a CStr> {
+        unsafe {
+            let mut attr = MaybeUninit::uninit();
+            let attr = dw_sys::dwarf_attr_integrate(self.as_ptr(), name, attr.as_mut_ptr());
+            if attr.is_null() {
+                return None;
+            }
+
+            let ptr = dw_sys::dwarf_formstring(attr);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(ptr))
+            }
+        }
+    }
+
+    fn udata_attr(&self, name: c_uint) -> Option<u64> {
+        unsafe {
+            let mut attr = MaybeUninit::uninit();
+            let attr = dw_sys::dwarf_attr(self.as_ptr(), name, attr.as_mut_ptr());
+            if attr.is_null() {
+                return None;
+            }
+
+            let mut value = 0;
+            cvt(dw_sys::dwarf_formudata(attr, &mut value)).ok()?;
+            Some(value)
+        }
+    }
+}
@@ -0,0 +1,17 @@
+//! DWARF types.
+
+use libc::c_int;
+
+pub use self::die::*;
+pub use self::error::*;
+
+mod die;
+mod error;
+
+fn cvt(r: c_int) -> Result<(), Error> {
+    if r == 0 {
+        Ok(())
+    } else {
+        Err(Error::new())
+    }
+}
@@ -43,6 +43,11 @@ pub struct FindElf(
 impl FindElf {
     /// A standard callback used with `Register::linux_proc`.
     pub const LINUX_PROC: FindElf = FindElf(dw_sys::dwfl_linux_proc_find_elf);
+
+    /// A standard callback used with `Report::core_file`.
+    ///
+    /// It locates ELF images by the build IDs recorded in the core file.
+    pub const BUILD_ID: FindElf = FindElf(dw_sys::dwfl_build_id_find_elf);
 }
 
 /// The callback responsible for locating the debuginfo of a process.
@@ -1,11 +1,14 @@
 use foreign_types::{foreign_type, ForeignType, ForeignTypeRef};
-use libc::{c_int, c_void, pid_t};
+use libc::{c_char, c_int, c_void, pid_t};
 use std::any::Any;
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
+use std::os::unix::ffi::OsStrExt;
 use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
 use std::ptr;
 
 use crate::dwfl::{cvt, Callbacks, Error, FrameRef, ModuleRef, ThreadRef};
+use crate::elf::Elf;
 
 foreign_type! {
     /// The base type used when interacting with libdwfl.
@@ -78,6 +81,25 @@ impl<'a> DwflRef<'a> {
         }
     }
 
+    /// Configures the session to unwind the threads of a process from an ELF core dump.
+    ///
+    /// The core file's segments and modules should first be registered via `Report::core_file`.
+    pub fn core_file_attach(&mut self, elf: &'a Elf) -> Result<(), Error> {
+        unsafe { cvt(dw_sys::dwfl_core_file_attach(self.as_ptr(), elf.as_ptr())) }
+    }
+
+    /// Returns the ID of the attached process.
+    pub fn pid(&self) -> Result<u32, Error> {
+        unsafe {
+            let pid = dw_sys::dwfl_pid(self.as_ptr());
+            if pid < 0 {
+                Err(Error::new())
+            } else {
+                Ok(pid as u32)
+            }
+        }
+    }
+
     /// Iterates through the threads of the attached process.
     ///
     /// The callback will be invoked for each thread in turn.
@@ -139,6 +161,41 @@ impl<'a> DwflRef<'a> {
         }
     }
 
+    /// Iterates through the modules registered with the session.
+    ///
+    /// The callback will be invoked for each module in turn.
+    pub fn modules<F>(&self, callback: F) -> Result<(), Error>
+    where
+        F: FnMut(&ModuleRef) -> Result<(), Error>,
+    {
+        unsafe {
+            let mut state = ModulesCallbackState {
+                callback,
+                panic: None,
+                error: None,
+            };
+            let r = dw_sys::dwfl_getmodules(
+                self.as_ptr(),
+                Some(modules_cb::<F>),
+                &mut state as *mut _ as *mut c_void,
+                0,
+            );
+
+            if let Some(payload) = state.panic {
+                panic::resume_unwind(payload);
+            }
+            if let Some(e) = state.error {
+                return Err(e);
+            }
+
+            if r < 0 {
+                Err(Error::new())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
     /// Looks up the module containing the address.
     pub fn addr_module(&self, address: u64) -> Result<&ModuleRef, Error> {
         unsafe {
@@ -175,6 +232,30 @@ impl<'a, 'b> Report<'a, 'b> {
             ))
         }
     }
+
+    /// Registers the segments and modules of a process from an ELF core dump.
+    ///
+    /// If the path to the process's main executable is not provided, libdwfl will attempt to locate it from
+    /// information in the core file. The `FindElf::BUILD_ID` callback should be used with this method.
+    pub fn core_file(&mut self, elf: &'b Elf, executable: Option<&Path>) -> Result<(), Error> {
+        let executable = match executable {
+            Some(p) => Some(CString::new(p.as_os_str().as_bytes()).map_err(|_| Error::nul_path())?),
+            None => None,
+        };
+
+        unsafe {
+            let r = dw_sys::dwfl_core_file_report(
+                self.0.as_ptr(),
+                elf.as_ptr(),
+                executable.as_ref().map_or(ptr::null(), |p| p.as_ptr()),
+            );
+            if r < 0 {
+                Err(Error::new())
+            } else {
+                Ok(())
+            }
+        }
+    }
 }
 
 struct ThreadsCallbackState<F> {
@@ -203,6 +284,38 @@ where
     }
 }
 
+struct ModulesCallbackState<F> {
+    callback: F,
+    panic: Option<Box<dyn Any + Send>>,
+    error: Option<Error>,
+}
+
+unsafe extern "C" fn modules_cb<F>(
+    module: *mut dw_sys::Dwfl_Module,
+    _: *mut *mut c_void,
+    _: *const c_char,
+    _: dw_sys::Dwarf_Addr,
+    arg: *mut c_void,
+) -> c_int
+where
+    F: FnMut(&ModuleRef) -> Result<(), Error>,
+{
+    let state = &mut *(arg as *mut ModulesCallbackState<F>);
+    let module = ModuleRef::from_ptr(module);
+
+    match panic::catch_unwind(AssertUnwindSafe(|| (state.callback)(module))) {
+        Ok(Ok(())) => dw_sys::DWARF_CB_OK,
+        Ok(Err(e)) => {
+            state.error = Some(e);
+            dw_sys::DWARF_CB_ABORT
+        }
+        Err(e) => {
+            state.panic = Some(e);
+            dw_sys::DWARF_CB_ABORT
+        }
+    }
+}
+
 struct FramesCallbackState<F> {
     callback: F,
     panic: Option<Box<dyn Any + Send>>,
@@ -3,15 +3,24 @@ use std::error;
 use std::ffi::CStr;
 use std::fmt;
 
+#[derive(Copy, Clone)]
+enum Repr {
+    Code(c_int),
+    Abort,
+    NulPath,
+}
+
 /// A error returned by DWFL APIs.
-pub struct Error(c_int);
+#[derive(Copy, Clone)]
+pub struct Error(Repr);
 
 impl fmt::Debug for Error {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        fmt.debug_struct("Error")
-            .field("code", &self.0)
-            .field("message", &self.as_str())
-            .finish()
+        let mut debug = fmt.debug_struct("Error");
+        if let Repr::Code(code) = self.0 {
+            debug.field("code", &code);
+        }
+        debug.field("message", &self.as_str()).finish()
     }
 }
 
@@ -25,12 +34,37 @@ impl error::Error for Error {}
 
 impl Error {
     pub(crate) fn new() -> Error {
-        unsafe { Error(dw_sys::dwfl_errno()) }
+        unsafe { Error(Repr::Code(dw_sys::dwfl_errno())) }
+    }
+
+    /// Returns an error which can be returned from an iteration callback to stop the iteration early.
+    ///
+    /// The iteration method will return the error unchanged.
+    pub fn abort() -> Error {
+        Error(Repr::Abort)
+    }
+
+    pub(crate) fn nul_path() -> Error {
+        Error(Repr::NulPath)
+    }
+
+    /// Determines if this error was created by `Error::abort`.
+    pub fn is_abort(&self) -> bool {
+        match self.0 {
+            Repr::Abort => true,
+            Repr::Code(_) | Repr::NulPath => false,
+        }
     }
 
     fn as_str(&self) -> &str {
+        let code = match self.0 {
+            Repr::Code(code) => code,
+            Repr::Abort => return "iteration aborted",
+            Repr::NulPath => return "path contains a nul byte",
+        };
+
         unsafe {
-            let s = dw_sys::dwfl_errmsg(self.0);
+            let s = dw_sys::dwfl_errmsg(code);
             if s.is_null() {
                 "unknown error"
             } else {
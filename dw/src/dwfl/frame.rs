@@ -1,4 +1,6 @@
 use foreign_types::{ForeignTypeRef, Opaque};
+#[cfg(not(pre188))]
+use libc::c_uint;
 use std::ptr;
 
 use crate::dwfl::{Error, ThreadRef};
@@ -34,4 +36,36 @@ impl FrameRef {
             }
         }
     }
+
+    /// Returns the value of a register at this frame.
+    ///
+    /// The register is identified by its DWARF register number. `None` is returned if the register's value could not
+    /// be recovered at this frame.
+    ///
+    /// Reading registers requires elfutils 0.188 or newer. `None` is always returned when built against older
+    /// versions.
+    #[cfg(not(pre188))]
+    pub fn register(&self, regno: u32) -> Result<Option<u64>, Error> {
+        unsafe {
+            let mut val = 0;
+            match dw_sys::dwfl_frame_reg(self.as_ptr(), regno as c_uint, &mut val) {
+                0 => Ok(Some(val)),
+                1 => Ok(None),
+                _ => Err(Error::new()),
+            }
+        }
+    }
+
+    /// Returns the value of a register at this frame.
+    ///
+    /// The register is identified by its DWARF register number. `None` is returned if the register's value could not
+    /// be recovered at this frame.
+    ///
+    /// Reading registers requires elfutils 0.188 or newer. `None` is always returned when built against older
+    /// versions.
+    #[cfg(pre188)]
+    pub fn register(&self, regno: u32) -> Result<Option<u64>, Error> {
+        let _ = regno;
+        Ok(None)
+    }
 }
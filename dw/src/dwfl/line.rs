@@ -0,0 +1,73 @@
+use foreign_types::{ForeignTypeRef, Opaque};
+use std::ffi::CStr;
+use std::ptr;
+
+use crate::dwfl::Error;
+
+/// A reference to an entry in a module's line table.
+pub struct LineRef(Opaque);
+
+unsafe impl ForeignTypeRef for LineRef {
+    type CType = dw_sys::Dwfl_Line;
+}
+
+impl LineRef {
+    /// Returns the source information associated with this line table entry.
+    pub fn info(&self) -> Result<LineInfo<'_>, Error> {
+        unsafe {
+            let mut address = 0;
+            let mut line = 0;
+            let mut column = 0;
+
+            let ptr = dw_sys::dwfl_lineinfo(
+                self.as_ptr(),
+                &mut address,
+                &mut line,
+                &mut column,
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+
+            if ptr.is_null() {
+                Err(Error::new())
+            } else {
+                Ok(LineInfo {
+                    file: CStr::from_ptr(ptr),
+                    address,
+                    line,
+                    column,
+                })
+            }
+        }
+    }
+}
+
+/// Source information about a line table entry.
+pub struct LineInfo<'a> {
+    file: &'a CStr,
+    address: u64,
+    line: i32,
+    column: i32,
+}
+
+impl<'a> LineInfo<'a> {
+    /// Returns the path of the source file.
+    pub fn file(&self) -> &'a CStr {
+        self.file
+    }
+
+    /// Returns the address of the start of the line table entry.
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    /// Returns the line number, or 0 if unknown.
+    pub fn line(&self) -> i32 {
+        self.line
+    }
+
+    /// Returns the column number, or 0 if unknown.
+    pub fn column(&self) -> i32 {
+        self.column
+    }
+}
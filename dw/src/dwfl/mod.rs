@@ -6,6 +6,7 @@ pub use self::callbacks::*;
 pub use self::dwfl::*;
 pub use self::error::*;
 pub use self::frame::*;
+pub use self::line::*;
 pub use self::module::*;
 pub use self::thread::*;
 
@@ -13,6 +14,7 @@ mod callbacks;
 mod dwfl;
 mod error;
 mod frame;
+mod line;
 mod module;
 mod thread;
 
@@ -1,9 +1,12 @@
 use foreign_types::{ForeignTypeRef, Opaque};
 use std::ffi::CStr;
 use std::mem;
+use std::ops::Range;
 use std::ptr;
+use std::slice;
 
-use crate::dwfl::Error;
+use crate::dwarf::Die;
+use crate::dwfl::{Error, LineRef};
 use crate::elf::Symbol;
 
 /// A reference to a module.
@@ -14,6 +17,44 @@ unsafe impl ForeignTypeRef for ModuleRef {
 }
 
 impl ModuleRef {
+    /// Returns the name of the module.
+    ///
+    /// For the modules of a running process, this is the path of the module's file.
+    pub fn name(&self) -> &CStr {
+        unsafe {
+            let ptr = dw_sys::dwfl_module_info(
+                self.as_ptr(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            CStr::from_ptr(ptr)
+        }
+    }
+
+    /// Returns the range of addresses the module is loaded at.
+    pub fn address_range(&self) -> Range<u64> {
+        unsafe {
+            let mut start = 0;
+            let mut end = 0;
+            dw_sys::dwfl_module_info(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut start,
+                &mut end,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+                ptr::null_mut(),
+            );
+            start..end
+        }
+    }
+
     /// Returns the name of the containing the address.
     pub fn addr_name(&self, addr: u64) -> Result<&CStr, Error> {
         unsafe {
@@ -55,6 +96,49 @@ impl ModuleRef {
             }
         }
     }
+
+    /// Returns the module's GNU build ID, if it has one.
+    pub fn build_id(&self) -> Result<Option<&[u8]>, Error> {
+        unsafe {
+            let mut bits = ptr::null();
+            let mut vaddr = 0;
+            let len = dw_sys::dwfl_module_build_id(self.as_ptr(), &mut bits, &mut vaddr);
+            if len < 0 {
+                Err(Error::new())
+            } else if len == 0 {
+                Ok(None)
+            } else {
+                Ok(Some(slice::from_raw_parts(bits, len as usize)))
+            }
+        }
+    }
+
+    /// Returns the line table entry containing the address.
+    pub fn addr_line(&self, addr: u64) -> Result<&LineRef, Error> {
+        unsafe {
+            let ptr = dw_sys::dwfl_module_getsrc(self.as_ptr(), addr);
+            if ptr.is_null() {
+                Err(Error::new())
+            } else {
+                Ok(LineRef::from_ptr(ptr))
+            }
+        }
+    }
+
+    /// Returns the compilation unit entry containing the address, along with the module's bias.
+    ///
+    /// Addresses within the compilation unit's debugging information are relative to the bias.
+    pub fn addr_die(&self, addr: u64) -> Result<(Die<'_>, u64), Error> {
+        unsafe {
+            let mut bias = 0;
+            let ptr = dw_sys::dwfl_module_addrdie(self.as_ptr(), addr, &mut bias);
+            if ptr.is_null() {
+                Err(Error::new())
+            } else {
+                Ok((Die::from_raw(*ptr), bias))
+            }
+        }
+    }
 }
 
 /// Information about a symbol.
@@ -0,0 +1,41 @@
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+use std::ptr;
+
+use crate::elf::Error;
+
+/// An ELF file opened for reading.
+pub struct Elf {
+    ptr: *mut dw_sys::Elf,
+    // libelf reads from the descriptor lazily, so it needs to stay open for the lifetime of the handle
+    _file: File,
+}
+
+impl Drop for Elf {
+    fn drop(&mut self) {
+        unsafe {
+            dw_sys::elf_end(self.ptr);
+        }
+    }
+}
+
+impl Elf {
+    /// Creates a new `Elf` reading from the specified file.
+    pub fn from_file(file: File) -> Result<Elf, Error> {
+        unsafe {
+            dw_sys::elf_version(dw_sys::EV_CURRENT);
+
+            let ptr = dw_sys::elf_begin(file.as_raw_fd(), dw_sys::ELF_C_READ_MMAP, ptr::null_mut());
+            if ptr.is_null() {
+                Err(Error::new())
+            } else {
+                Ok(Elf { ptr, _file: file })
+            }
+        }
+    }
+
+    /// Returns the raw pointer to the underlying libelf handle.
+    pub fn as_ptr(&self) -> *mut dw_sys::Elf {
+        self.ptr
+    }
+}
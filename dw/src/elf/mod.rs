@@ -1,5 +1,9 @@
 //! ELF data types.
 
+pub use self::error::*;
+pub use self::file::*;
 pub use self::symbol::*;
 
+mod error;
+mod file;
 mod symbol;
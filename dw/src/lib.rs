@@ -2,6 +2,7 @@
 #![doc(html_root_url = "https://sfackler.github.io/rstack/doc")]
 #![warn(missing_docs)]
 
+pub mod dwarf;
 pub mod dwfl;
 pub mod elf;
 
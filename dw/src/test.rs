@@ -1,8 +1,33 @@
-use std::process::Command;
+use std::fs::File;
+use std::path::Path;
+use std::process::{Child, Command};
 use std::thread;
 use std::time::Duration;
 
+use crate::dwarf::Tag;
 use crate::dwfl::{Callbacks, Dwfl, Error, FindDebuginfo, FindElf, FrameRef};
+use crate::elf::Elf;
+
+struct ChildGuard(Child);
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+impl ChildGuard {
+    fn id(&self) -> u32 {
+        self.0.id()
+    }
+}
+
+fn spawn_sleep() -> ChildGuard {
+    let child = ChildGuard(Command::new("sleep").arg("10").spawn().unwrap());
+    thread::sleep(Duration::from_millis(10));
+    child
+}
 
 fn frame_callback(frame: &mut FrameRef) -> Result<(), Error> {
     let mut activation = false;
@@ -11,20 +36,39 @@ fn frame_callback(frame: &mut FrameRef) -> Result<(), Error> {
         ip -= 1;
     }
 
-    match frame
-        .thread()
-        .dwfl()
-        .addr_module(ip)
-        .and_then(|module| module.addr_info(ip))
-    {
-        Ok(info) => println!(
+    let module = frame.thread().dwfl().addr_module(ip).ok();
+    match module.and_then(|module| module.addr_info(ip).ok()) {
+        Some(info) => println!(
             "    {:#016x} - {} ({:#016x}) + {:#x}",
             ip,
             info.name().to_string_lossy(),
             info.bias() + info.symbol().value(),
             info.offset()
         ),
-        Err(_) => println!("    {:#016x} - ????", ip),
+        None => println!("    {:#016x} - ????", ip),
+    }
+
+    if let Some(info) = module
+        .and_then(|module| module.addr_line(ip).ok())
+        .and_then(|line| line.info().ok())
+    {
+        println!(
+            "        at {}:{}:{}",
+            info.file().to_string_lossy(),
+            info.line(),
+            info.column()
+        );
+    }
+
+    if let Some((cu, bias)) = module.and_then(|module| module.addr_die(ip).ok()) {
+        for scope in cu.scopes(ip - bias).unwrap() {
+            if scope.tag() == Tag::INLINED_SUBROUTINE {
+                println!(
+                    "        inlined {}",
+                    scope.name().map_or("????".into(), |n| n.to_string_lossy())
+                );
+            }
+        }
     }
 
     Ok(())
@@ -32,8 +76,7 @@ fn frame_callback(frame: &mut FrameRef) -> Result<(), Error> {
 
 #[test]
 fn trace_sleep() {
-    let child = Command::new("sleep").arg("10").spawn().unwrap();
-    thread::sleep(Duration::from_millis(10));
+    let child = spawn_sleep();
 
     let callbacks = Callbacks::new(FindElf::LINUX_PROC, FindDebuginfo::STANDARD);
     let mut dwfl = Dwfl::begin(&callbacks).unwrap();
@@ -49,8 +92,7 @@ fn trace_sleep() {
 
 #[test]
 fn trace_sleep_thread() {
-    let child = Command::new("sleep").arg("10").spawn().unwrap();
-    thread::sleep(Duration::from_millis(10));
+    let child = spawn_sleep();
 
     let callbacks = Callbacks::new(FindElf::LINUX_PROC, FindDebuginfo::STANDARD);
     let mut dwfl = Dwfl::begin(&callbacks).unwrap();
@@ -59,3 +101,37 @@ fn trace_sleep_thread() {
 
     dwfl.thread_frames(child.id(), frame_callback).unwrap();
 }
+
+#[test]
+fn abort_thread_frames() {
+    let child = spawn_sleep();
+
+    let callbacks = Callbacks::new(FindElf::LINUX_PROC, FindDebuginfo::STANDARD);
+    let mut dwfl = Dwfl::begin(&callbacks).unwrap();
+    dwfl.report().linux_proc(child.id()).unwrap();
+    dwfl.linux_proc_attach(child.id(), false).unwrap();
+
+    let mut frames = 0;
+    let e = dwfl
+        .thread_frames(child.id(), |_| {
+            frames += 1;
+            Err(Error::abort())
+        })
+        .unwrap_err();
+    assert!(e.is_abort());
+    assert_eq!(frames, 1);
+}
+
+#[test]
+fn core_file_nul_executable() {
+    let elf = Elf::from_file(File::open("/proc/self/exe").unwrap()).unwrap();
+
+    let callbacks = Callbacks::new(FindElf::BUILD_ID, FindDebuginfo::STANDARD);
+    let mut dwfl = Dwfl::begin(&callbacks).unwrap();
+    let e = dwfl
+        .report()
+        .core_file(&elf, Some(Path::new("foo\0bar")))
+        .unwrap_err();
+    assert!(!e.is_abort());
+    assert_eq!(e.to_string(), "path contains a nul byte");
+}
@@ -0,0 +1,381 @@
+//! A command line tool which prints stack traces of the threads of a running process.
+use clap::{Parser, Subcommand, ValueEnum};
+use libc::{EACCES, ENOENT, EPERM, ESRCH};
+use rstack::{Folded, GroupBy, Location, Pprof, Process, Thread, TraceOptions, Truncation};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+const EXIT_ERROR: i32 = 1;
+const EXIT_NO_SUCH_PROCESS: i32 = 3;
+const EXIT_PERMISSION_DENIED: i32 = 4;
+
+const AFTER_HELP: &str = "\
+EXIT STATUS:
+    0    The process was traced successfully
+    1    An unexpected error occurred
+    2    The arguments were invalid
+    3    The process does not exist
+    4    Permission to trace the process was denied";
+
+/// Prints stack traces of the threads of a running process.
+#[derive(Parser)]
+#[clap(
+    version,
+    after_help = AFTER_HELP,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
+struct Args {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    /// The ID of the process to trace.
+    #[clap(required = true)]
+    pid: Option<u32>,
+
+    #[clap(flatten)]
+    trace: TraceArgs,
+
+    /// The output format.
+    #[clap(long, value_enum, default_value = "text")]
+    format: Format,
+
+    /// Print threads with identical stacks once, in text output.
+    #[clap(long, value_enum, value_name = "BY")]
+    group: Option<Group>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Traces a process twice and reports which threads' stacks changed in between.
+    ///
+    /// Threads whose stacks are unchanged are likely to be blocked.
+    #[clap(after_help = AFTER_HELP)]
+    Diff(DiffArgs),
+}
+
+#[derive(clap::Args)]
+struct DiffArgs {
+    /// The ID of the process to trace.
+    pid: u32,
+
+    /// The number of seconds to wait between the two traces.
+    #[clap(long, value_name = "SECS", default_value = "5")]
+    interval: f64,
+
+    #[clap(flatten)]
+    trace: TraceArgs,
+}
+
+#[derive(clap::Args)]
+struct TraceArgs {
+    /// Stop all threads for the duration of the trace to capture a consistent snapshot.
+    #[clap(long)]
+    snapshot: bool,
+
+    /// Only trace the thread with this ID. May be repeated.
+    #[clap(long = "tid", value_name = "TID")]
+    tids: Vec<u32>,
+
+    /// Only trace threads whose name contains this string, where `*` and `?` act as wildcards.
+    #[clap(long, value_name = "NAME")]
+    name: Option<String>,
+
+    /// Don't look up the symbol of each frame.
+    #[clap(long)]
+    no_symbols: bool,
+
+    /// Look up the source location and inlined calls of each frame. Requires debug info.
+    #[clap(long)]
+    source_lines: bool,
+
+    /// Unwind at most this many frames of each thread.
+    #[clap(long, value_name = "N")]
+    max_depth: Option<usize>,
+
+    /// Stop unwinding a thread after this many milliseconds.
+    #[clap(long, value_name = "MS")]
+    unwind_timeout: Option<u64>,
+
+    /// Print the system call each thread was blocked in.
+    #[clap(long)]
+    syscalls: bool,
+}
+
+impl TraceArgs {
+    fn trace(&self, pid: u32) -> Process {
+        let mut options = TraceOptions::new();
+        if !self.tids.is_empty() {
+            options.threads(&self.tids);
+        }
+        if let Some(name) = &self.name {
+            options.thread_name_pattern(&format!("*{}*", name));
+        }
+        if let Some(max_depth) = self.max_depth {
+            options.max_frames(max_depth);
+        }
+        if let Some(unwind_timeout) = self.unwind_timeout {
+            options.unwind_timeout(Duration::from_millis(unwind_timeout));
+        }
+
+        match options
+            .snapshot(self.snapshot)
+            .thread_names(true)
+            .symbols(!self.no_symbols)
+            .source_lines(self.source_lines)
+            .inlined_frames(self.source_lines)
+            .blocking_info(self.syscalls)
+            .demangle(true)
+            .trace(pid)
+        {
+            Ok(process) => process,
+            Err(e) => {
+                eprintln!("error tracing process {}: {}", pid, e);
+                process::exit(exit_code(pid, &e));
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Group {
+    /// Group threads whose frames have identical instruction pointers.
+    Ips,
+    /// Group threads whose frames have identical symbol names.
+    Symbols,
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    /// Folded stacks, as consumed by flamegraph tools.
+    Folded,
+    /// An SVG flamegraph.
+    Flamegraph,
+    /// A gzip-compressed pprof protobuf profile.
+    Pprof,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let r = match (&args.command, args.pid) {
+        (Some(Command::Diff(args)), _) => diff(&mut stdout, args),
+        (None, Some(pid)) => {
+            let process = args.trace.trace(pid);
+            match args.format {
+                Format::Text => print_text(&mut stdout, &args, &process),
+                Format::Json => print_json(&mut stdout, &process),
+                Format::Folded => Folded::new().add_process(&process).write(&mut stdout),
+                Format::Flamegraph => Folded::new()
+                    .add_process(&process)
+                    .write_flamegraph(&mut stdout),
+                Format::Pprof => Pprof::new().add_process(&process).write(&mut stdout),
+            }
+        }
+        // clap requires a pid if there's no subcommand
+        (None, None) => unreachable!(),
+    };
+
+    match r {
+        Ok(()) => {}
+        Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(e) => {
+            eprintln!("error writing output: {}", e);
+            process::exit(EXIT_ERROR);
+        }
+    }
+}
+
+fn exit_code(pid: u32, e: &rstack::Error) -> i32 {
+    match e.as_io_error().and_then(|e| e.raw_os_error()) {
+        Some(ESRCH) | Some(ENOENT) => EXIT_NO_SUCH_PROCESS,
+        Some(EPERM) | Some(EACCES) => EXIT_PERMISSION_DENIED,
+        // the libdw backend reports its own errors when it can't find the process
+        _ if !Path::new(&format!("/proc/{}", pid)).exists() => EXIT_NO_SUCH_PROCESS,
+        _ => EXIT_ERROR,
+    }
+}
+
+fn print_text(w: &mut dyn Write, args: &Args, process: &Process) -> io::Result<()> {
+    if process.snapshot_converged() == Some(false) {
+        eprintln!("warning: unable to stop all threads, so the snapshot may be inconsistent");
+    }
+
+    let group_by = match args.group {
+        Some(Group::Ips) => GroupBy::Ips,
+        Some(Group::Symbols) => GroupBy::Symbols,
+        None => {
+            for thread in process.threads() {
+                print_thread_header(w, thread)?;
+                print_frames(w, thread)?;
+            }
+            return Ok(());
+        }
+    };
+
+    for group in process.group_threads(group_by) {
+        let threads = group.threads();
+
+        match threads {
+            [thread] => print_thread_header(w, thread)?,
+            threads => {
+                writeln!(w, "{} threads with this stack", threads.len())?;
+                let ids = threads
+                    .iter()
+                    .map(|t| t.id().to_string())
+                    .collect::<Vec<_>>();
+                writeln!(w, "  tids: {}", ids.join(", "))?;
+                writeln!(w, "  names: {}", format_names(threads))?;
+            }
+        }
+
+        // threads grouped by symbol may have differing frames, so print the first one
+        print_frames(w, threads[0])?;
+    }
+
+    Ok(())
+}
+
+fn print_thread_header(w: &mut dyn Write, thread: &Thread) -> io::Result<()> {
+    writeln!(
+        w,
+        "thread {} - {}",
+        thread.id(),
+        thread.name().unwrap_or("<unknown>")
+    )?;
+
+    if let Some(syscall) = thread.syscall() {
+        let args = syscall
+            .args()
+            .iter()
+            .map(|a| format!("{:#x}", a))
+            .collect::<Vec<_>>();
+        match syscall.name() {
+            Some(name) => writeln!(w, "  in syscall {}({})", name, args.join(", "))?,
+            None => writeln!(w, "  in syscall {}({})", syscall.number(), args.join(", "))?,
+        }
+    }
+
+    Ok(())
+}
+
+// summarizes thread names as "name (count)", in order of first appearance
+fn format_names(threads: &[&Thread]) -> String {
+    let mut names: Vec<(&str, usize)> = vec![];
+    for thread in threads {
+        let name = thread.name().unwrap_or("<unknown>");
+        match names.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, count)) => *count += 1,
+            None => names.push((name, 1)),
+        }
+    }
+
+    names
+        .iter()
+        .map(|(name, count)| format!("{} ({})", name, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_frames(w: &mut dyn Write, thread: &Thread) -> io::Result<()> {
+    for frame in thread.frames() {
+        for inlined in frame.inlined_frames() {
+            let name = inlined
+                .demangled_name()
+                .or_else(|| inlined.name())
+                .unwrap_or("???");
+            writeln!(w, "{:18} - {} (inlined)", "", name)?;
+            if let Some(location) = inlined.call_location() {
+                writeln!(w, "{:18}   at {}", "", format_location(location))?;
+            }
+        }
+
+        match frame.symbol() {
+            Some(symbol) => writeln!(
+                w,
+                "{:#018x} - {} + {:#x}",
+                frame.ip(),
+                symbol.demangled_name().unwrap_or_else(|| symbol.name()),
+                symbol.offset(),
+            )?,
+            None => writeln!(w, "{:#018x} - ???", frame.ip())?,
+        }
+        if let Some(location) = frame.location() {
+            writeln!(w, "{:18}   at {}", "", format_location(location))?;
+        }
+    }
+
+    match thread.truncation() {
+        Some(Truncation::MaxFrames) => writeln!(w, "{:18}   (maximum depth reached)", "")?,
+        Some(Truncation::Timeout) => writeln!(w, "{:18}   (unwinding timed out)", "")?,
+        None => {}
+    }
+    if let Some(error) = thread.error() {
+        writeln!(w, "{:18}   ({})", "", error)?;
+    }
+
+    writeln!(w)
+}
+
+fn format_location(location: &Location) -> String {
+    let mut s = location.file().display().to_string();
+    if let Some(line) = location.line() {
+        s.push_str(&format!(":{}", line));
+        if let Some(column) = location.column() {
+            s.push_str(&format!(":{}", column));
+        }
+    }
+    s
+}
+
+fn print_json(w: &mut dyn Write, process: &Process) -> io::Result<()> {
+    serde_json::to_writer_pretty(&mut *w, process)?;
+    writeln!(w)
+}
+
+fn diff(w: &mut dyn Write, args: &DiffArgs) -> io::Result<()> {
+    let before = args.trace.trace(args.pid);
+    thread::sleep(Duration::from_secs_f64(args.interval));
+    let after = args.trace.trace(args.pid);
+
+    if before.snapshot_converged() == Some(false) || after.snapshot_converged() == Some(false) {
+        eprintln!("warning: unable to stop all threads, so the snapshots may be inconsistent");
+    }
+
+    let diff = before.diff(&after);
+
+    let exited = diff.exited().iter().copied();
+    print_section(w, "exited threads", exited)?;
+
+    let created = diff.created().iter().copied();
+    print_section(w, "created threads", created)?;
+
+    let changed = diff.changed().iter().map(|d| d.after());
+    print_section(w, "threads with changed stacks", changed)?;
+
+    writeln!(w, "threads with unchanged stacks:")?;
+    for thread in diff.unchanged().iter().map(|d| d.after()) {
+        print_thread_header(w, thread)?;
+        print_frames(w, thread)?;
+    }
+
+    Ok(())
+}
+
+fn print_section<'a, I>(w: &mut dyn Write, title: &str, threads: I) -> io::Result<()>
+where
+    I: IntoIterator<Item = &'a Thread>,
+{
+    writeln!(w, "{}:", title)?;
+    for thread in threads {
+        print_thread_header(w, thread)?;
+    }
+    writeln!(w)
+}
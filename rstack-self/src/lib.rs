@@ -54,7 +54,9 @@
 use antidote::Mutex;
 use lazy_static::lazy_static;
 use libc::{c_ulong, getppid, prctl, PR_SET_PTRACER};
-use serde::{Deserialize, Serialize};
+use serde_::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
 use std::error;
 use std::fmt;
 use std::io::{self, BufReader, Read, Write};
@@ -86,7 +88,15 @@ impl error::Error for Error {
 }
 
 /// A trace of the threads in a process.
+///
+/// With the `serde` Cargo feature enabled, this type implements `Serialize` and `Deserialize`. The serialized form
+/// includes a schema version, and deserialization fails for traces with an unsupported version.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_", into = "TraceRepr", try_from = "TraceRepr")
+)]
 pub struct Trace {
     threads: Vec<Thread>,
 }
@@ -98,8 +108,54 @@ impl Trace {
     }
 }
 
+// The version of the serialized trace schema, which must be incremented whenever the serialized form changes
+// incompatibly.
+#[cfg(feature = "serde")]
+const SCHEMA_VERSION: u32 = 1;
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
+struct TraceRepr {
+    version: u32,
+    threads: Vec<Thread>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Trace> for TraceRepr {
+    fn from(trace: Trace) -> TraceRepr {
+        TraceRepr {
+            version: SCHEMA_VERSION,
+            threads: trace.threads,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<TraceRepr> for Trace {
+    type Error = String;
+
+    fn try_from(repr: TraceRepr) -> result::Result<Trace, String> {
+        if repr.version != SCHEMA_VERSION {
+            return Err(format!(
+                "unsupported trace schema version {} (expected {})",
+                repr.version, SCHEMA_VERSION
+            ));
+        }
+
+        Ok(Trace {
+            threads: repr.threads,
+        })
+    }
+}
+
 /// Information about a thread.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_")
+)]
 pub struct Thread {
     id: u32,
     name: String,
@@ -125,6 +181,11 @@ impl Thread {
 
 /// Information about a stack frame.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_")
+)]
 pub struct Frame {
     ip: usize,
     symbols: Vec<Symbol>,
@@ -146,6 +207,11 @@ impl Frame {
 
 /// Information about a symbol.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_")
+)]
 pub struct Symbol {
     name: Option<String>,
     file: Option<PathBuf>,
@@ -324,11 +390,13 @@ fn symbolicate_thread(raw: RawThread) -> Thread {
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
 struct RawOptions {
     snapshot: bool,
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
 struct RawThread {
     id: u32,
     name: String,
@@ -336,6 +404,7 @@ struct RawThread {
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(crate = "serde_")]
 struct RawFrame {
     ip: u64,
     is_signal: bool,
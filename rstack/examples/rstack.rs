@@ -16,7 +16,12 @@ fn main() {
         }
     };
 
-    let process = match rstack::trace(pid) {
+    let process = match rstack::TraceOptions::new()
+        .thread_names(true)
+        .symbols(true)
+        .demangle(true)
+        .trace(pid)
+    {
         Ok(threads) => threads,
         Err(e) => {
             eprintln!("error tracing threads: {}", e);
@@ -35,7 +40,7 @@ fn main() {
                 Some(symbol) => println!(
                     "{:#016x} - {} + {:#x}",
                     frame.ip(),
-                    symbol.name(),
+                    symbol.demangled_name().unwrap_or_else(|| symbol.name()),
                     symbol.offset(),
                 ),
                 None => println!("{:#016x} - ???", frame.ip()),
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use crate::{Process, Thread};
+
+/// A comparison of two traces of the same process, with threads matched by ID.
+#[derive(Debug, Clone)]
+pub struct ProcessDiff<'a> {
+    created: Vec<&'a Thread>,
+    exited: Vec<&'a Thread>,
+    unchanged: Vec<ThreadDiff<'a>>,
+    changed: Vec<ThreadDiff<'a>>,
+}
+
+impl<'a> ProcessDiff<'a> {
+    /// Returns the threads present only in the later trace.
+    #[inline]
+    pub fn created(&self) -> &[&'a Thread] {
+        &self.created
+    }
+
+    /// Returns the threads present only in the earlier trace.
+    #[inline]
+    pub fn exited(&self) -> &[&'a Thread] {
+        &self.exited
+    }
+
+    /// Returns the threads whose stacks are identical in both traces.
+    ///
+    /// These threads are likely to be blocked.
+    #[inline]
+    pub fn unchanged(&self) -> &[ThreadDiff<'a>] {
+        &self.unchanged
+    }
+
+    /// Returns the threads whose stacks differ between the traces.
+    #[inline]
+    pub fn changed(&self) -> &[ThreadDiff<'a>] {
+        &self.changed
+    }
+}
+
+/// A thread present in both of two traces.
+#[derive(Debug, Clone)]
+pub struct ThreadDiff<'a> {
+    before: &'a Thread,
+    after: &'a Thread,
+}
+
+impl<'a> ThreadDiff<'a> {
+    /// Returns the thread as it appeared in the earlier trace.
+    #[inline]
+    pub fn before(&self) -> &'a Thread {
+        self.before
+    }
+
+    /// Returns the thread as it appeared in the later trace.
+    #[inline]
+    pub fn after(&self) -> &'a Thread {
+        self.after
+    }
+}
+
+pub fn diff<'a>(before: &'a Process, after: &'a Process) -> ProcessDiff<'a> {
+    let mut diff = ProcessDiff {
+        created: vec![],
+        exited: vec![],
+        unchanged: vec![],
+        changed: vec![],
+    };
+
+    let mut remaining = after
+        .threads()
+        .iter()
+        .map(|t| (t.id(), t))
+        .collect::<HashMap<_, _>>();
+
+    for thread in before.threads() {
+        let after = match remaining.remove(&thread.id()) {
+            Some(after) => after,
+            None => {
+                diff.exited.push(thread);
+                continue;
+            }
+        };
+
+        let thread_diff = ThreadDiff {
+            before: thread,
+            after,
+        };
+        // stacks are compared by instruction pointer since symbols may not have been looked up
+        let unchanged = thread.frames().len() == after.frames().len()
+            && thread
+                .frames()
+                .iter()
+                .zip(after.frames())
+                .all(|(a, b)| a.ip() == b.ip());
+        if unchanged {
+            diff.unchanged.push(thread_diff);
+        } else {
+            diff.changed.push(thread_diff);
+        }
+    }
+
+    // preserve the order of the later trace
+    diff.created = after
+        .threads()
+        .iter()
+        .filter(|t| remaining.contains_key(&t.id()))
+        .collect();
+
+    diff
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{frames, process, thread};
+
+    fn ids(threads: &[&Thread]) -> Vec<u32> {
+        threads.iter().map(|t| t.id()).collect()
+    }
+
+    fn diff_ids(diffs: &[ThreadDiff<'_>]) -> Vec<u32> {
+        diffs.iter().map(|d| d.before().id()).collect()
+    }
+
+    #[test]
+    fn classification() {
+        let before = process(vec![
+            thread(1, frames(&[0x10, 0x20])),
+            thread(2, frames(&[0x30, 0x20])),
+            thread(3, frames(&[0x40])),
+            thread(4, frames(&[0x50, 0x20])),
+        ]);
+        let after = process(vec![
+            thread(6, frames(&[0x10])),
+            thread(4, frames(&[0x20])),
+            thread(1, frames(&[0x10, 0x20])),
+            thread(5, frames(&[0x10])),
+            thread(2, frames(&[0x31, 0x20])),
+        ]);
+
+        let diff = diff(&before, &after);
+        assert_eq!(ids(diff.exited()), [3]);
+        // created threads are in the order of the later trace
+        assert_eq!(ids(diff.created()), [6, 5]);
+        assert_eq!(diff_ids(diff.unchanged()), [1]);
+        // both a changed instruction pointer and a changed depth count as a change
+        assert_eq!(diff_ids(diff.changed()), [2, 4]);
+        assert_eq!(diff.changed()[1].after().frames().len(), 1);
+    }
+
+    #[test]
+    fn identical() {
+        let before = process(vec![thread(1, frames(&[0x10])), thread(2, vec![])]);
+        let after = process(vec![thread(2, vec![]), thread(1, frames(&[0x10]))]);
+
+        let diff = diff(&before, &after);
+        assert!(diff.created().is_empty());
+        assert!(diff.exited().is_empty());
+        assert!(diff.changed().is_empty());
+        assert_eq!(diff_ids(diff.unchanged()), [1, 2]);
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use crate::{Frame, Process, Profile};
+
+/// An exporter which aggregates stack traces into Brendan Gregg's folded stack format.
+///
+/// Each line of the output consists of a thread name followed by the thread's frames from outermost to innermost,
+/// all separated by semicolons, and finally the number of times that stack was observed:
+///
+/// ```text
+/// main;_start;main;foo;bar 3
+/// ```
+///
+/// This is the input format of tools like [FlameGraph] and [inferno]. With the `flamegraph` Cargo feature enabled,
+/// an SVG flamegraph can also be rendered directly.
+///
+/// [FlameGraph]: https://github.com/brendangregg/FlameGraph
+/// [inferno]: https://github.com/jonhoo/inferno
+#[derive(Debug, Clone, Default)]
+pub struct Folded {
+    stacks: BTreeMap<String, u64>,
+}
+
+impl Folded {
+    /// Returns a new, empty `Folded`.
+    pub fn new() -> Folded {
+        Folded::default()
+    }
+
+    /// Adds the stacks of the threads of a traced process, each with a count of 1.
+    pub fn add_process(&mut self, process: &Process) -> &mut Folded {
+        for thread in process.threads() {
+            self.add_stack(thread.name(), thread.frames(), 1);
+        }
+        self
+    }
+
+    /// Adds the stacks of a sampled process, each with the number of times it was sampled.
+    pub fn add_profile(&mut self, profile: &Profile) -> &mut Folded {
+        for stack in profile.stacks() {
+            self.add_stack(stack.thread_name(), stack.frames(), stack.count());
+        }
+        self
+    }
+
+    /// Adds a single stack with the specified count.
+    ///
+    /// The frames are ordered innermost first, as returned by `Thread::frames`.
+    pub fn add_stack(
+        &mut self,
+        thread_name: Option<&str>,
+        frames: &[Frame],
+        count: u64,
+    ) -> &mut Folded {
+        let mut line = String::new();
+        push_name(&mut line, thread_name);
+
+        for frame in frames.iter().rev() {
+            line.push(';');
+            let name = frame
+                .symbol()
+                .map(|s| s.demangled_name().unwrap_or_else(|| s.name()));
+            push_name(&mut line, name);
+
+            // inlined frames are ordered innermost first
+            for inlined in frame.inlined_frames().iter().rev() {
+                line.push(';');
+                push_name(
+                    &mut line,
+                    inlined.demangled_name().or_else(|| inlined.name()),
+                );
+            }
+        }
+
+        *self.stacks.entry(line).or_insert(0) += count;
+        self
+    }
+
+    /// Returns an iterator over the folded stacks and their counts.
+    pub fn stacks(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.stacks.iter().map(|(stack, count)| (&**stack, *count))
+    }
+
+    /// Writes the folded stacks, one per line.
+    pub fn write<W>(&self, mut w: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        for (stack, count) in self.stacks() {
+            writeln!(w, "{} {}", stack, count)?;
+        }
+        Ok(())
+    }
+
+    /// Renders the folded stacks as an SVG flamegraph.
+    ///
+    /// Requires the `flamegraph` Cargo feature.
+    #[cfg(feature = "flamegraph")]
+    pub fn write_flamegraph<W>(&self, w: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let lines = self
+            .stacks()
+            .map(|(stack, count)| format!("{} {}", stack, count))
+            .collect::<Vec<_>>();
+
+        let mut options = inferno::flamegraph::Options::default();
+        inferno::flamegraph::from_lines(&mut options, lines.iter().map(|l| &**l), w)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
+
+// semicolons separate frames and newlines separate stacks, so neither can appear in a name. Spaces are fine since the
+// count is split off at the last one.
+fn push_name(line: &mut String, name: Option<&str>) {
+    match name {
+        Some(name) => line.extend(name.chars().map(|c| match c {
+            ';' => ':',
+            '\n' | '\r' => ' ',
+            c => c,
+        })),
+        None => line.push_str("[unknown]"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{self, frame};
+
+    fn output(folded: &Folded) -> String {
+        let mut buf = vec![];
+        folded.write(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn outermost_first() {
+        let frames = [
+            frame(3, Some("bar")),
+            frame(2, Some("foo")),
+            frame(1, Some("main")),
+        ];
+
+        let mut folded = Folded::new();
+        folded.add_stack(Some("main"), &frames, 3);
+        assert_eq!(output(&folded), "main;main;foo;bar 3\n");
+    }
+
+    #[test]
+    fn inlined_frames() {
+        // `outer` was inlined into `caller`, and `inner` into `outer`
+        let mut caller = frame(2, Some("caller"));
+        caller.inlined_frames = test_util::inlined_frames(&["inner", "outer"]);
+        let frames = [caller, frame(1, Some("main"))];
+
+        let mut folded = Folded::new();
+        folded.add_stack(Some("main"), &frames, 1);
+        assert_eq!(output(&folded), "main;main;caller;outer;inner 1\n");
+    }
+
+    #[test]
+    fn unknown_and_escaped_names() {
+        let frames = [frame(2, None), frame(1, Some("ns::foo(int; char)"))];
+
+        let mut folded = Folded::new();
+        folded.add_stack(Some("worker\n1"), &frames, 1);
+        assert_eq!(
+            output(&folded),
+            "worker 1;[unknown];ns::foo(int: char);[unknown] 1\n"
+        );
+    }
+
+    #[test]
+    fn counts_aggregated() {
+        let a = [frame(2, Some("foo")), frame(1, Some("main"))];
+        let b = [frame(3, Some("bar")), frame(1, Some("main"))];
+
+        let mut folded = Folded::new();
+        folded
+            .add_stack(Some("worker"), &a, 2)
+            .add_stack(Some("worker"), &b, 1)
+            .add_stack(Some("worker"), &a, 5)
+            .add_stack(Some("other thread"), &a, 1);
+        assert_eq!(
+            output(&folded),
+            "other thread;main;foo 1\nworker;main;bar 1\nworker;main;foo 7\n"
+        );
+    }
+}
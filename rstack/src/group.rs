@@ -0,0 +1,169 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{Frame, Process, Thread};
+
+/// The criteria used to decide whether two threads have the same stack.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GroupBy {
+    /// Threads are grouped if the instruction pointers of their frames are identical.
+    Ips,
+    /// Threads are grouped if the symbol names of their frames are identical.
+    ///
+    /// This merges threads which are at different offsets within the same functions. Frames without a symbol are
+    /// compared by instruction pointer.
+    Symbols,
+}
+
+/// A group of threads with identical stacks.
+#[derive(Debug, Clone)]
+pub struct ThreadGroup<'a> {
+    threads: Vec<&'a Thread>,
+}
+
+impl<'a> ThreadGroup<'a> {
+    /// Returns the threads in the group, in the order they appear in the process.
+    #[inline]
+    pub fn threads(&self) -> &[&'a Thread] {
+        &self.threads
+    }
+
+    /// Returns the frames of the first thread in the group.
+    ///
+    /// When grouping by symbol name, the other threads' frames may differ in their instruction pointers.
+    #[inline]
+    pub fn frames(&self) -> &'a [Frame] {
+        self.threads[0].frames()
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum FrameKey<'a> {
+    Ip(u64),
+    Symbol(&'a str),
+}
+
+fn frame_key(frame: &Frame, by: GroupBy) -> FrameKey<'_> {
+    match (by, frame.symbol()) {
+        (GroupBy::Symbols, Some(symbol)) => FrameKey::Symbol(symbol.name()),
+        _ => FrameKey::Ip(frame.ip()),
+    }
+}
+
+pub fn group_threads(process: &Process, by: GroupBy) -> Vec<ThreadGroup<'_>> {
+    let mut groups = vec![];
+    let mut indices = HashMap::new();
+
+    for thread in process.threads() {
+        let key = thread
+            .frames()
+            .iter()
+            .map(|f| frame_key(f, by))
+            .collect::<Vec<_>>();
+        let idx = *indices.entry(key).or_insert_with(|| {
+            groups.push(ThreadGroup { threads: vec![] });
+            groups.len() - 1
+        });
+        groups[idx].threads.push(thread);
+    }
+
+    // the sort is stable, so groups of the same size stay in order of first appearance
+    groups.sort_by_key(|g| Reverse(g.threads.len()));
+    groups
+}
+
+/// A group of threads which were waiting on the same futex.
+#[derive(Debug, Clone)]
+pub struct FutexWaiters<'a> {
+    address: u64,
+    threads: Vec<&'a Thread>,
+}
+
+impl<'a> FutexWaiters<'a> {
+    /// Returns the address of the futex.
+    #[inline]
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    /// Returns the threads waiting on the futex, in the order they appear in the process.
+    #[inline]
+    pub fn threads(&self) -> &[&'a Thread] {
+        &self.threads
+    }
+}
+
+pub fn futex_waiters(process: &Process) -> Vec<FutexWaiters<'_>> {
+    let mut waiters = BTreeMap::new();
+    for thread in process.threads() {
+        if let Some(address) = thread.futex_address() {
+            waiters.entry(address).or_insert_with(Vec::new).push(thread);
+        }
+    }
+
+    let mut waiters = waiters
+        .into_iter()
+        .map(|(address, threads)| FutexWaiters { address, threads })
+        .collect::<Vec<_>>();
+    waiters.sort_by_key(|w| Reverse(w.threads.len()));
+    waiters
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::{frame, process, thread};
+
+    fn ids(groups: &[ThreadGroup<'_>]) -> Vec<Vec<u32>> {
+        groups
+            .iter()
+            .map(|g| g.threads().iter().map(|t| t.id()).collect())
+            .collect()
+    }
+
+    fn test_process() -> Process {
+        process(vec![
+            thread(1, vec![frame(0x1010, Some("main"))]),
+            thread(
+                2,
+                vec![frame(0x2010, Some("wait")), frame(0x3010, Some("worker"))],
+            ),
+            thread(
+                3,
+                vec![frame(0x2020, Some("wait")), frame(0x3010, Some("worker"))],
+            ),
+            thread(
+                4,
+                vec![frame(0x2010, Some("wait")), frame(0x3010, Some("worker"))],
+            ),
+            thread(5, vec![frame(0x4010, None), frame(0x3010, Some("worker"))]),
+            thread(6, vec![frame(0x4020, None), frame(0x3010, Some("worker"))]),
+        ])
+    }
+
+    #[test]
+    fn group_by_ips() {
+        let process = test_process();
+        let groups = group_threads(&process, GroupBy::Ips);
+        assert_eq!(
+            ids(&groups),
+            [vec![2, 4], vec![1], vec![3], vec![5], vec![6]]
+        );
+        assert_eq!(groups[0].frames()[0].ip(), 0x2010);
+    }
+
+    #[test]
+    fn group_by_symbols() {
+        let process = test_process();
+        let groups = group_threads(&process, GroupBy::Symbols);
+        // frames without symbols are still compared by instruction pointer
+        assert_eq!(ids(&groups), [vec![2, 3, 4], vec![1], vec![5], vec![6]]);
+    }
+
+    #[test]
+    fn group_empty_stacks() {
+        let process = process(vec![thread(1, vec![]), thread(2, vec![])]);
+        let groups = group_threads(&process, GroupBy::Ips);
+        assert_eq!(ids(&groups), [vec![1, 2]]);
+    }
+}
@@ -0,0 +1,232 @@
+//! DWARF debug information lookups for the libunwind backend.
+//!
+//! libunwind only knows about symbol tables, so source locations and inlined calls are resolved separately by mapping
+//! an instruction pointer back to the ELF file it was loaded from via `/proc/<pid>/maps`, and then consulting that
+//! file's debug information.
+//!
+//! Files are opened through `/proc/<pid>/root` so that processes in other mount namespaces, such as containers, are
+//! resolved against their own filesystem. Separate debug information is only looked up by build ID under
+//! `/usr/lib/debug/.build-id` in that filesystem; `.gnu_debuglink` sections and debuginfod are not consulted.
+use addr2line::fallible_iterator::FallibleIterator;
+use addr2line::gimli::{EndianRcSlice, RunTimeEndian};
+use addr2line::object::{self, Object, ObjectSegment};
+use addr2line::Context;
+use log::debug;
+use std::collections::HashMap;
+use std::error;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use unwind_::MapsEntry;
+
+use crate::{InlinedFrame, Location};
+
+struct Segment {
+    address: u64,
+    offset: u64,
+    size: u64,
+}
+
+struct Module {
+    context: Context<EndianRcSlice<RunTimeEndian>>,
+    segments: Vec<Segment>,
+}
+
+impl Module {
+    fn open(pid: u32, path: &Path) -> Result<Module, Box<dyn error::Error + Sync + Send>> {
+        let data = fs::read(process_path(pid, path))?;
+        let file = object::File::parse(&*data)?;
+
+        let segments = file
+            .segments()
+            .map(|s| {
+                let (offset, size) = s.file_range();
+                Segment {
+                    address: s.address(),
+                    offset,
+                    size,
+                }
+            })
+            .collect();
+
+        // stripped binaries may have their debug info split out into a separate file
+        let context = match debug_file(pid, &file) {
+            Some(data) => Context::new(&object::File::parse(&*data)?)?,
+            None => Context::new(&file)?,
+        };
+
+        Ok(Module { context, segments })
+    }
+
+    // translates an offset into the file into the address the linker assigned to it
+    fn address(&self, offset: u64) -> Option<u64> {
+        self.segments
+            .iter()
+            .find(|s| s.offset <= offset && offset < s.offset + s.size)
+            .map(|s| s.address + (offset - s.offset))
+    }
+}
+
+// resolves a path as seen by the process, which may be in a different mount namespace than us
+fn process_path(pid: u32, path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::from(format!("/proc/{}/root", pid));
+    resolved.push(path.strip_prefix("/").unwrap_or(path));
+    resolved
+}
+
+/// Returns the GNU build ID of the ELF file at the specified path in a process's filesystem, if it has one.
+#[cfg(feature = "pprof")]
+pub fn build_id(pid: u32, path: &Path) -> Option<Vec<u8>> {
+    let data = fs::read(process_path(pid, path)).ok()?;
+    let file = object::File::parse(&*data).ok()?;
+    file.build_id().ok()?.map(|id| id.to_vec())
+}
+
+fn debug_file(pid: u32, file: &object::File<'_>) -> Option<Vec<u8>> {
+    if file.section_by_name(".debug_info").is_some() {
+        return None;
+    }
+
+    let build_id = file.build_id().ok()??;
+    if build_id.len() < 2 {
+        return None;
+    }
+
+    let mut path = format!("/usr/lib/debug/.build-id/{:02x}/", build_id[0]);
+    for b in &build_id[1..] {
+        let _ = write!(path, "{:02x}", b);
+    }
+    path.push_str(".debug");
+
+    fs::read(process_path(pid, Path::new(&path))).ok()
+}
+
+pub struct Debuginfo {
+    pid: u32,
+    maps: Vec<MapsEntry>,
+    modules: HashMap<PathBuf, Option<Module>>,
+}
+
+impl Debuginfo {
+    pub fn new(pid: u32) -> Debuginfo {
+        Debuginfo {
+            pid,
+            maps: vec![],
+            modules: HashMap::new(),
+        }
+    }
+
+    /// Returns the source location of the instruction at the specified address.
+    pub fn location(&mut self, addr: u64) -> Option<Location> {
+        let (module, addr) = self.module(addr)?;
+
+        match module.context.find_location(addr) {
+            Ok(Some(l)) => location(&l),
+            Ok(None) => None,
+            Err(e) => {
+                debug!("error looking up location of {:#016x}: {}", addr, e);
+                None
+            }
+        }
+    }
+
+    /// Returns the function calls inlined at the specified address, innermost first.
+    pub fn inlined_frames(&mut self, addr: u64) -> Vec<InlinedFrame> {
+        let (module, addr) = match self.module(addr) {
+            Some(module) => module,
+            None => return vec![],
+        };
+
+        let frames = module
+            .context
+            .find_frames(addr)
+            .and_then(|frames| frames.collect::<Vec<_>>());
+        let frames = match frames {
+            Ok(frames) => frames,
+            Err(e) => {
+                debug!("error looking up inlined frames of {:#016x}: {}", addr, e);
+                return vec![];
+            }
+        };
+
+        // each frame's location is within its own function, so the call site of an inlined function is the location
+        // of the frame after it
+        frames
+            .windows(2)
+            .map(|w| InlinedFrame {
+                name: w[0]
+                    .function
+                    .as_ref()
+                    .and_then(|f| f.raw_name().ok())
+                    .map(|n| n.into_owned()),
+                demangled_name: None,
+                call_location: w[1].location.as_ref().and_then(location),
+            })
+            .collect()
+    }
+
+    // returns the module containing the address along with the address translated into the module's address space
+    fn module(&mut self, addr: u64) -> Option<(&Module, u64)> {
+        let idx = match self.map_index(addr) {
+            Some(idx) => idx,
+            None => {
+                // the process may have loaded new libraries since we last looked
+                self.refresh_maps();
+                self.map_index(addr)?
+            }
+        };
+        let map = &self.maps[idx];
+        let path = map.path()?;
+        let pid = self.pid;
+
+        let module = self
+            .modules
+            .entry(path.to_path_buf())
+            .or_insert_with(|| match Module::open(pid, path) {
+                Ok(module) => Some(module),
+                Err(e) => {
+                    debug!("error loading debuginfo for {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .as_ref()?;
+
+        let addr = module.address(addr - map.start() + map.offset())?;
+        Some((module, addr))
+    }
+
+    fn map_index(&self, addr: u64) -> Option<usize> {
+        self.maps
+            .iter()
+            .position(|m| m.start() <= addr && addr < m.end())
+    }
+
+    /// Rereads the process's memory mappings, returning true if they changed.
+    pub fn refresh_maps(&mut self) -> bool {
+        let maps = match MapsEntry::read(self.pid) {
+            Ok(maps) => maps
+                .into_iter()
+                .filter(|m| m.is_executable() && m.path().is_some())
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                debug!("error reading maps of process {}: {}", self.pid, e);
+                return false;
+            }
+        };
+
+        if maps == self.maps {
+            return false;
+        }
+
+        self.maps = maps;
+        true
+    }
+}
+
+fn location(location: &addr2line::Location<'_>) -> Option<Location> {
+    location.file.map(|file| Location {
+        file: PathBuf::from(file),
+        line: location.line,
+        column: location.column,
+    })
+}
@@ -1,47 +1,255 @@
+use cfg_if::cfg_if;
+use dw_::dwarf::Tag;
 pub use dw_::dwfl::Error;
-use dw_::dwfl::{Callbacks, Dwfl, FindDebuginfo, FindElf};
+use dw_::dwfl::{Callbacks, Dwfl, FindDebuginfo, FindElf, FrameRef, ModuleRef};
+use dw_::elf::Elf;
 use lazy_static::lazy_static;
+use log::debug;
+use std::borrow::Cow;
+use std::ffi::{CStr, OsStr};
+use std::fs::File;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-use crate::{Frame, Symbol, TraceOptions, TracedThread};
+#[cfg(feature = "pprof")]
+use crate::pprof::Map;
+use crate::{
+    ErrorInner, Frame, InlinedFrame, Location, Process, Register, Registers, Symbol, Thread,
+    TraceOptions, TracedThread, Truncation, UnwindError,
+};
+
+// DWARF register numbers
+cfg_if! {
+    if #[cfg(target_arch = "x86_64")] {
+        const SP: Option<u32> = Some(7);
+        const CALLEE_SAVED: &[(&str, u32)] = &[
+            ("rbx", 3),
+            ("rbp", 6),
+            ("r12", 12),
+            ("r13", 13),
+            ("r14", 14),
+            ("r15", 15),
+        ];
+    } else if #[cfg(target_arch = "aarch64")] {
+        const SP: Option<u32> = Some(31);
+        const CALLEE_SAVED: &[(&str, u32)] = &[
+            ("x19", 19),
+            ("x20", 20),
+            ("x21", 21),
+            ("x22", 22),
+            ("x23", 23),
+            ("x24", 24),
+            ("x25", 25),
+            ("x26", 26),
+            ("x27", 27),
+            ("x28", 28),
+            ("x29", 29),
+        ];
+    } else {
+        const SP: Option<u32> = None;
+        const CALLEE_SAVED: &[(&str, u32)] = &[];
+    }
+}
 
 lazy_static! {
     static ref CALLBACKS: Callbacks = Callbacks::new(FindElf::LINUX_PROC, FindDebuginfo::STANDARD);
+    static ref CORE_CALLBACKS: Callbacks =
+        Callbacks::new(FindElf::BUILD_ID, FindDebuginfo::STANDARD);
 }
 
-pub struct State(Dwfl<'static>);
+pub struct State {
+    dwfl: Dwfl<'static>,
+    pid: u32,
+}
 
 impl State {
     pub fn new(pid: u32) -> Result<State, Error> {
         let mut dwfl = Dwfl::begin(&*CALLBACKS)?;
         dwfl.report().linux_proc(pid)?;
         dwfl.linux_proc_attach(pid, true)?;
-        Ok(State(dwfl))
+        Ok(State { dwfl, pid })
+    }
+
+    pub fn refresh(&mut self) -> Result<(), Error> {
+        // modules which are reported again are reused rather than reloaded
+        self.dwfl.report().linux_proc(self.pid)
+    }
+}
+
+/// Reads the file-backed executable memory mappings of a process.
+///
+/// libdwfl reports a single mapping for each module of the process, covering all of its segments.
+#[cfg(feature = "pprof")]
+pub fn executable_maps(pid: u32) -> io::Result<Vec<Map>> {
+    let mut dwfl = Dwfl::begin(&CALLBACKS).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    dwfl.report()
+        .linux_proc(pid)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+    let mut maps = vec![];
+    dwfl.modules(|module| {
+        let name = module.name();
+        // skip the vDSO, and files which have been deleted since they were mapped
+        if name.to_bytes().starts_with(b"/") && !name.to_bytes().ends_with(b" (deleted)") {
+            let range = module.address_range();
+            maps.push(Map {
+                start: range.start,
+                end: range.end,
+                // modules start at the mapping of their first segment, which starts at the beginning of the file
+                offset: 0,
+                path: path(name),
+            });
+        }
+        Ok(())
+    })
+    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+    Ok(maps)
+}
+
+/// Returns the build IDs of the files backing the specified mappings of a process.
+#[cfg(feature = "pprof")]
+pub fn build_ids(pid: u32, maps: &[Map]) -> Vec<Option<Vec<u8>>> {
+    let mut dwfl = match Dwfl::begin(&CALLBACKS) {
+        Ok(dwfl) => dwfl,
+        Err(e) => {
+            debug!("error creating Dwfl session: {}", e);
+            return vec![None; maps.len()];
+        }
+    };
+    if let Err(e) = dwfl.report().linux_proc(pid) {
+        debug!("error reporting modules of process {}: {}", pid, e);
+        return vec![None; maps.len()];
     }
+
+    maps.iter()
+        .map(|m| {
+            let module = dwfl.addr_module(m.start).ok()?;
+            module.build_id().ok()?.map(|id| id.to_vec())
+        })
+        .collect()
 }
 
 impl TracedThread {
     pub fn dump_inner(
         &self,
-        dwfl: &mut State,
+        state: &mut State,
         options: &TraceOptions,
         frames: &mut Vec<Frame>,
-    ) -> Result<(), Error> {
-        dwfl.0.thread_frames(self.id, |frame| {
-            let mut is_signal = false;
-            let ip = frame.pc(Some(&mut is_signal))?;
+    ) -> Result<Option<Truncation>, Error> {
+        frames_with_limits(frames, options, |callback| {
+            state.dwfl.thread_frames(self.id, callback)
+        })
+    }
+}
+
+pub fn trace_core(
+    core: &Path,
+    executable: Option<&Path>,
+    options: &TraceOptions,
+) -> crate::Result<Process> {
+    let file = File::open(core).map_err(|e| crate::Error(ErrorInner::Io(e)))?;
+    let elf = Elf::from_file(file).map_err(|e| {
+        crate::Error(ErrorInner::Io(io::Error::new(
+            io::ErrorKind::InvalidData,
+            e,
+        )))
+    })?;
+
+    let mut dwfl = Dwfl::begin(&CORE_CALLBACKS).map_err(|e| crate::Error(ErrorInner::Unwind(e)))?;
+    dwfl.report()
+        .core_file(&elf, executable)
+        .map_err(|e| crate::Error(ErrorInner::Unwind(e)))?;
+    dwfl.core_file_attach(&elf)
+        .map_err(|e| crate::Error(ErrorInner::Unwind(e)))?;
+    let id = dwfl
+        .pid()
+        .map_err(|e| crate::Error(ErrorInner::Unwind(e)))?;
+
+    let mut threads = vec![];
+    dwfl.threads(|thread| {
+        let mut frames = vec![];
+        let r = frames_with_limits(&mut frames, options, |callback| thread.frames(callback));
+        let (truncation, error) = match r {
+            Ok(truncation) => (truncation, None),
+            Err(error) => {
+                debug!("error tracing thread {}: {}", thread.tid(), error);
+                let error = UnwindError::new(error, frames.len());
+                (None, Some(error))
+            }
+        };
+        crate::fill_cfas(&mut frames);
+        options.demangle_frames(&mut frames);
+
+        threads.push(Thread {
+            id: thread.tid(),
+            // thread names aren't recorded in core dumps
+            name: None,
+            sched_info: None,
+            kernel_stack: None,
+            syscall: None,
+            truncation,
+            error,
+            frames,
+        });
+        Ok(())
+    })
+    .map_err(|e| crate::Error(ErrorInner::Unwind(e)))?;
+
+    Ok(Process {
+        id,
+        threads,
+        snapshot_converged: None,
+    })
+}
 
-            let mut symbol = None;
+// drives a frame iteration, aborting it once the trace options' limits are reached
+fn frames_with_limits<F>(
+    frames: &mut Vec<Frame>,
+    options: &TraceOptions,
+    iterate: F,
+) -> Result<Option<Truncation>, Error>
+where
+    F: FnOnce(&mut dyn FnMut(&mut FrameRef) -> Result<(), Error>) -> Result<(), Error>,
+{
+    let start = Instant::now();
+    let mut truncation = None;
+
+    let r = iterate(&mut |frame| {
+        frames.push(self::frame(frame, options)?);
+        truncation = options.truncation(frames.len(), start);
+        match truncation {
+            Some(_) => Err(Error::abort()),
+            None => Ok(()),
+        }
+    });
+
+    match r {
+        Ok(()) => Ok(None),
+        Err(ref e) if e.is_abort() => Ok(truncation),
+        Err(e) => Err(e),
+    }
+}
+
+fn frame(frame: &mut FrameRef, options: &TraceOptions) -> Result<Frame, Error> {
+    let mut is_signal = false;
+    let ip = frame.pc(Some(&mut is_signal))?;
+
+    let mut symbol = None;
+    let mut location = None;
+    let mut inlined_frames = vec![];
+    if options.symbols || options.source_lines || options.inlined_frames {
+        let signal_adjust = if is_signal { 0 } else { 1 };
+        let addr = ip - signal_adjust;
+
+        if let Ok(module) = frame.thread().dwfl().addr_module(addr) {
             if options.symbols {
-                let signal_adjust = if is_signal { 0 } else { 1 };
-
-                if let Ok(i) = frame
-                    .thread()
-                    .dwfl()
-                    .addr_module(ip - signal_adjust)
-                    .and_then(|module| module.addr_info(ip - signal_adjust))
-                {
+                if let Ok(i) = module.addr_info(addr) {
                     symbol = Some(Symbol {
                         name: i.name().to_string_lossy().into_owned(),
+                        demangled_name: None,
                         offset: i.offset() + signal_adjust,
                         address: i.bias() + i.symbol().value(),
                         size: i.symbol().size(),
@@ -49,13 +257,93 @@ impl TracedThread {
                 }
             }
 
-            frames.push(Frame {
-                ip,
-                is_signal,
-                symbol,
-            });
+            if options.source_lines {
+                if let Ok(i) = module.addr_line(addr).and_then(|l| l.info()) {
+                    location = Some(Location {
+                        file: path(i.file()),
+                        line: positive(i.line()),
+                        column: positive(i.column()),
+                    });
+                }
+            }
+
+            if options.inlined_frames {
+                inlined_frames = inlined(module, addr);
+            }
+        }
+    }
+
+    let registers = if options.registers {
+        Some(registers(frame))
+    } else {
+        None
+    };
+
+    Ok(Frame {
+        ip,
+        is_signal,
+        symbol,
+        location,
+        inlined_frames,
+        registers,
+    })
+}
+
+fn registers(frame: &FrameRef) -> Registers {
+    Registers {
+        sp: SP.and_then(|r| frame.register(r).ok().flatten()),
+        // libdw doesn't expose the CFA directly, so it's filled in from the caller's stack pointer after unwinding
+        cfa: None,
+        callee_saved: CALLEE_SAVED
+            .iter()
+            .filter_map(|&(name, r)| {
+                frame.register(r).ok().flatten().map(|value| Register {
+                    name: Cow::Borrowed(name),
+                    value,
+                })
+            })
+            .collect(),
+    }
+}
 
-            Ok(())
+fn inlined(module: &ModuleRef, addr: u64) -> Vec<InlinedFrame> {
+    let (cu, bias) = match module.addr_die(addr) {
+        Ok(cu) => cu,
+        Err(_) => return vec![],
+    };
+
+    let scopes = match cu.scopes(addr - bias) {
+        Ok(scopes) => scopes,
+        Err(_) => return vec![],
+    };
+
+    scopes
+        .iter()
+        .filter(|s| s.tag() == Tag::INLINED_SUBROUTINE)
+        .map(|s| InlinedFrame {
+            name: s
+                .linkage_name()
+                .or_else(|| s.name())
+                .map(|n| n.to_string_lossy().into_owned()),
+            demangled_name: None,
+            call_location: s.call_file().map(|file| Location {
+                file: path(file),
+                line: s.call_line().filter(|l| *l > 0).map(|l| l as u32),
+                column: s.call_column().filter(|c| *c > 0).map(|c| c as u32),
+            }),
         })
+        .collect()
+}
+
+fn path(s: &CStr) -> PathBuf {
+    PathBuf::from(OsStr::from_bytes(s.to_bytes()))
+}
+
+// libdw uses 0 to indicate an unknown line or column
+fn positive(v: i32) -> Option<u32> {
+    if v > 0 {
+        Some(v as u32)
+    } else {
+        None
     }
 }
@@ -1,25 +1,121 @@
+use cfg_if::cfg_if;
+use std::borrow::Cow;
+#[cfg(feature = "pprof")]
+use std::collections::HashMap;
+#[cfg(feature = "pprof")]
+use std::io;
+use std::time::Instant;
 pub use unwind_::Error;
+#[cfg(feature = "pprof")]
+use unwind_::MapsEntry;
 use unwind_::{Accessors, AddressSpace, Byteorder, Cursor, PTraceState, PTraceStateRef, RegNum};
 
-use crate::{Frame, Symbol, TraceOptions, TracedThread};
+use crate::imp::debuginfo::Debuginfo;
+#[cfg(feature = "pprof")]
+use crate::pprof::Map;
+use crate::{Frame, Register, Registers, Symbol, TraceOptions, TracedThread, Truncation};
 
-pub struct State(AddressSpace<PTraceStateRef>);
+mod debuginfo;
+
+cfg_if! {
+    if #[cfg(target_arch = "x86_64")] {
+        const CFA: Option<RegNum> = Some(RegNum::CFA);
+        const CALLEE_SAVED: &[(&str, RegNum)] = &[
+            ("rbx", RegNum::RBX),
+            ("rbp", RegNum::RBP),
+            ("r12", RegNum::R12),
+            ("r13", RegNum::R13),
+            ("r14", RegNum::R14),
+            ("r15", RegNum::R15),
+        ];
+    } else if #[cfg(target_arch = "aarch64")] {
+        // libunwind's aarch64 CFA register is an alias of SP, so the CFA is instead filled in from the caller's stack
+        // pointer after unwinding
+        const CFA: Option<RegNum> = None;
+        const CALLEE_SAVED: &[(&str, RegNum)] = &[
+            ("x19", RegNum::X19),
+            ("x20", RegNum::X20),
+            ("x21", RegNum::X21),
+            ("x22", RegNum::X22),
+            ("x23", RegNum::X23),
+            ("x24", RegNum::X24),
+            ("x25", RegNum::X25),
+            ("x26", RegNum::X26),
+            ("x27", RegNum::X27),
+            ("x28", RegNum::X28),
+            ("x29", RegNum::X29),
+        ];
+    } else {
+        const CFA: Option<RegNum> = None;
+        const CALLEE_SAVED: &[(&str, RegNum)] = &[];
+    }
+}
+
+pub struct State {
+    space: AddressSpace<PTraceStateRef>,
+    debuginfo: Debuginfo,
+}
 
 impl State {
-    pub fn new(_: u32) -> Result<State, Error> {
-        AddressSpace::new(Accessors::ptrace(), Byteorder::DEFAULT).map(State)
+    pub fn new(pid: u32) -> Result<State, Error> {
+        let space = AddressSpace::new(Accessors::ptrace(), Byteorder::DEFAULT)?;
+        Ok(State {
+            space,
+            debuginfo: Debuginfo::new(pid),
+        })
     }
+
+    pub fn refresh(&mut self) -> Result<(), Error> {
+        // cached unwind info is only invalidated by changes to the process's mappings
+        if self.debuginfo.refresh_maps() {
+            self.space.flush_cache();
+        }
+        Ok(())
+    }
+}
+
+/// Reads the file-backed executable memory mappings of a process.
+#[cfg(feature = "pprof")]
+pub fn executable_maps(pid: u32) -> io::Result<Vec<Map>> {
+    let maps = MapsEntry::read(pid)?
+        .into_iter()
+        .filter(|m| m.is_executable())
+        .filter_map(|m| {
+            m.path().map(|path| Map {
+                start: m.start(),
+                end: m.end(),
+                offset: m.offset(),
+                path: path.to_path_buf(),
+            })
+        })
+        .collect();
+    Ok(maps)
+}
+
+/// Returns the build IDs of the files backing the specified mappings of a process.
+#[cfg(feature = "pprof")]
+pub fn build_ids(pid: u32, maps: &[Map]) -> Vec<Option<Vec<u8>>> {
+    let mut cache = HashMap::new();
+    maps.iter()
+        .map(|m| {
+            cache
+                .entry(&m.path)
+                .or_insert_with(|| debuginfo::build_id(pid, &m.path))
+                .clone()
+        })
+        .collect()
 }
 
 impl TracedThread {
     pub fn dump_inner(
         &self,
-        space: &mut State,
+        state: &mut State,
         options: &TraceOptions,
         frames: &mut Vec<Frame>,
-    ) -> Result<(), Error> {
-        let state = PTraceState::new(self.id)?;
-        let mut cursor = Cursor::remote(&space.0, &state)?;
+    ) -> Result<Option<Truncation>, Error> {
+        let start = Instant::now();
+        let ptrace_state = PTraceState::new(self.id)?;
+        let mut cursor = Cursor::remote(&state.space, &ptrace_state)?;
 
         loop {
             let ip = cursor.register(RegNum::IP)?;
@@ -31,6 +127,7 @@ impl TracedThread {
                     (Ok(ref name), Ok(ref info)) if info.start_ip() + name.offset() == ip => {
                         symbol = Some(Symbol {
                             name: name.name().to_string(),
+                            demangled_name: None,
                             offset: name.offset(),
                             address: info.start_ip(),
                             size: info.end_ip() - info.start_ip(),
@@ -40,17 +137,60 @@ impl TracedThread {
                 }
             }
 
+            let mut location = None;
+            let mut inlined_frames = vec![];
+            if options.source_lines || options.inlined_frames {
+                let signal_adjust = if is_signal || ip == 0 { 0 } else { 1 };
+                let addr = ip - signal_adjust;
+
+                if options.source_lines {
+                    location = state.debuginfo.location(addr);
+                }
+                if options.inlined_frames {
+                    inlined_frames = state.debuginfo.inlined_frames(addr);
+                }
+            }
+
+            let registers = if options.registers {
+                Some(registers(&mut cursor))
+            } else {
+                None
+            };
+
             frames.push(Frame {
                 ip,
                 is_signal,
                 symbol,
+                location,
+                inlined_frames,
+                registers,
             });
 
+            if let Some(truncation) = options.truncation(frames.len(), start) {
+                return Ok(Some(truncation));
+            }
+
             if !cursor.step()? {
                 break;
             }
         }
 
-        Ok(())
+        Ok(None)
+    }
+}
+
+fn registers(cursor: &mut Cursor<'_>) -> Registers {
+    Registers {
+        sp: cursor.register(RegNum::SP).ok(),
+        cfa: CFA.and_then(|r| cursor.register(r).ok()),
+        callee_saved: CALLEE_SAVED
+            .iter()
+            .filter_map(|&(name, r)| {
+                cursor.register(r).ok().map(|value| Register {
+                    name: Cow::Borrowed(name),
+                    value,
+                })
+            })
+            .collect(),
     }
 }
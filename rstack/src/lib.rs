@@ -14,6 +14,10 @@
 //! rstack = { version = "0.1", features = ["dw"], default-features = false }
 //! ```
 //!
+//! The `serde` Cargo feature enables serialization of traces, and the `flamegraph` Cargo feature enables rendering
+//! SVG flamegraphs with `Folded::write_flamegraph`. The `pprof` Cargo feature enables export to pprof's protobuf format
+//! with `Pprof`.
+//!
 //! [libunwind]: http://www.nongnu.org/libunwind/
 //! [elfutils]: https://sourceware.org/elfutils/
 #![doc(html_root_url = "https://sfackler.github.io/rstack/doc")]
@@ -21,19 +25,24 @@
 
 use cfg_if::cfg_if;
 use libc::{
-    c_void, pid_t, ptrace, waitpid, ESRCH, PTRACE_ATTACH, PTRACE_CONT, PTRACE_DETACH,
-    PTRACE_INTERRUPT, PTRACE_SEIZE, SIGSTOP, WIFSTOPPED, WSTOPSIG, __WALL,
+    c_int, c_ulong, c_void, pid_t, ptrace, waitpid, ESRCH, PTRACE_ATTACH, PTRACE_CONT,
+    PTRACE_DETACH, PTRACE_EVENT_CLONE, PTRACE_EVENT_STOP, PTRACE_GETEVENTMSG, PTRACE_INTERRUPT,
+    PTRACE_O_TRACECLONE, PTRACE_SEIZE, SIGSTOP, WIFSTOPPED, WSTOPSIG, __WALL,
 };
 use log::debug;
-use std::borrow::Borrow;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::borrow::{Borrow, Cow};
 use std::cmp::Ordering;
 use std::collections::BTreeSet;
 use std::error;
 use std::fmt;
-use std::fs::{self, File};
-use std::io::{self, Read};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::ptr;
 use std::result;
+use std::time::{Duration, Instant};
 
 cfg_if! {
     if #[cfg(feature = "dw")] {
@@ -47,6 +56,26 @@ cfg_if! {
     }
 }
 
+pub use crate::diff::{ProcessDiff, ThreadDiff};
+pub use crate::folded::Folded;
+pub use crate::group::{FutexWaiters, GroupBy, ThreadGroup};
+#[cfg(feature = "pprof")]
+pub use crate::pprof::Pprof;
+pub use crate::sampler::{Profile, SampledStack, Sampler};
+
+mod diff;
+mod folded;
+mod group;
+#[cfg(feature = "pprof")]
+mod pprof;
+mod proc;
+mod sampler;
+#[cfg(feature = "serde")]
+mod schema;
+mod syscall;
+#[cfg(test)]
+mod test_util;
+
 /// The result type returned by methods in this crate.
 pub type Result<T> = result::Result<T, Error>;
 
@@ -69,6 +98,19 @@ impl fmt::Display for Error {
     }
 }
 
+impl Error {
+    /// Returns the underlying IO error, if this error was caused by one.
+    ///
+    /// This can be used to distinguish failures like a nonexistent process (`ESRCH`) from insufficient permissions to
+    /// trace it (`EPERM`).
+    pub fn as_io_error(&self) -> Option<&io::Error> {
+        match self.0 {
+            ErrorInner::Io(ref e) => Some(e),
+            ErrorInner::Unwind(_) => None,
+        }
+    }
+}
+
 impl error::Error for Error {
     fn cause(&self) -> Option<&dyn error::Error> {
         match self.0 {
@@ -79,10 +121,19 @@ impl error::Error for Error {
 }
 
 /// Information about a remote process.
+///
+/// With the `serde` Cargo feature enabled, this type implements `Serialize` and `Deserialize`. The serialized form
+/// includes a schema version, and deserialization fails for traces with an unsupported version.
 #[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(into = "schema::ProcessRepr", try_from = "schema::ProcessRepr")
+)]
 pub struct Process {
     id: u32,
     threads: Vec<Thread>,
+    snapshot_converged: Option<bool>,
 }
 
 impl Process {
@@ -95,13 +146,53 @@ impl Process {
     pub fn threads(&self) -> &[Thread] {
         &self.threads
     }
+
+    /// Returns whether a snapshot-mode trace managed to stop every thread of the process.
+    ///
+    /// A snapshot will fail to converge if the process continuously creates new threads faster than they can be
+    /// stopped, in which case some of the traced threads may have been running while others were being traced.
+    ///
+    /// Returns `None` if the trace was not taken in snapshot mode.
+    pub fn snapshot_converged(&self) -> Option<bool> {
+        self.snapshot_converged
+    }
+
+    /// Groups the threads of the process which have identical stacks.
+    ///
+    /// Groups are ordered from largest to smallest. This is useful to condense the output for processes with large
+    /// pools of idle threads.
+    pub fn group_threads(&self, by: GroupBy) -> Vec<ThreadGroup<'_>> {
+        group::group_threads(self, by)
+    }
+
+    /// Groups the threads of the process which were waiting on the same futex.
+    ///
+    /// Groups are ordered from largest to smallest. Only threads traced with `TraceOptions::blocking_info` enabled
+    /// are included. Many threads waiting on the same lock may indicate contention or a deadlock.
+    pub fn futex_waiters(&self) -> Vec<FutexWaiters<'_>> {
+        group::futex_waiters(self)
+    }
+
+    /// Compares this trace against a later trace of the same process.
+    ///
+    /// Threads are matched by ID, and their stacks are compared by the instruction pointers of their frames. Taking
+    /// two traces a few seconds apart is a useful way to identify threads which are stuck.
+    pub fn diff<'a>(&'a self, later: &'a Process) -> ProcessDiff<'a> {
+        diff::diff(self, later)
+    }
 }
 
 /// Information about a thread of a remote process.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Thread {
     id: u32,
     name: Option<String>,
+    sched_info: Option<SchedInfo>,
+    kernel_stack: Option<result::Result<Vec<KernelFrame>, KernelStackError>>,
+    syscall: Option<Syscall>,
+    truncation: Option<Truncation>,
+    error: Option<UnwindError>,
     frames: Vec<Frame>,
 }
 
@@ -118,6 +209,62 @@ impl Thread {
         self.name.as_ref().map(|s| &**s)
     }
 
+    /// Returns the thread's scheduler state at the time it was traced, if recorded.
+    #[inline]
+    pub fn sched_info(&self) -> Option<&SchedInfo> {
+        self.sched_info.as_ref()
+    }
+
+    /// Returns the frames of the thread's kernel stack at the time it was traced, innermost first, if recorded.
+    #[inline]
+    pub fn kernel_frames(&self) -> Option<&[KernelFrame]> {
+        match &self.kernel_stack {
+            Some(Ok(frames)) => Some(frames),
+            _ => None,
+        }
+    }
+
+    /// Returns the reason the thread's kernel stack could not be read, if recording it was requested and failed.
+    #[inline]
+    pub fn kernel_stack_error(&self) -> Option<&KernelStackError> {
+        match &self.kernel_stack {
+            Some(Err(e)) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Returns the system call the thread was blocked in when it was traced, if recorded.
+    ///
+    /// Like a frame's `is_signal` flag, this describes how the thread came to be where it is: a thread whose
+    /// innermost frame is a syscall wrapper was blocked in this syscall, such as `epoll_wait` or `read`.
+    #[inline]
+    pub fn syscall(&self) -> Option<&Syscall> {
+        self.syscall.as_ref()
+    }
+
+    /// Returns the address of the futex the thread was waiting on when it was traced, if recorded.
+    ///
+    /// Threads blocked on a `std::sync::Mutex`, a `parking_lot` lock, or most other synchronization primitives on
+    /// Linux will be waiting on a futex.
+    pub fn futex_address(&self) -> Option<u64> {
+        self.syscall.as_ref().and_then(syscall::futex_address)
+    }
+
+    /// Returns the reason the thread's stack trace was cut short, if it was.
+    #[inline]
+    pub fn truncation(&self) -> Option<Truncation> {
+        self.truncation
+    }
+
+    /// Returns the error which stopped the unwinding of the thread's stack, if one occurred.
+    ///
+    /// If set, the thread's frames are only the portion of the stack which was unwound before the error. This
+    /// distinguishes a failed unwind from a stack which was genuinely empty.
+    #[inline]
+    pub fn error(&self) -> Option<&UnwindError> {
+        self.error.as_ref()
+    }
+
     /// Returns the frames of the stack trace representing the state of the thread.
     #[inline]
     pub fn frames(&self) -> &[Frame] {
@@ -125,12 +272,287 @@ impl Thread {
     }
 }
 
+/// An error which stopped the unwinding of a thread's stack.
+///
+/// The underlying error from the unwinding backend is available via `Error::source`, except for errors which were
+/// deserialized.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UnwindError {
+    message: String,
+    frame: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    error: Option<imp::Error>,
+}
+
+impl fmt::Display for UnwindError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "error unwinding frame {}: {}", self.frame, self.message)
+    }
+}
+
+impl error::Error for UnwindError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match &self.error {
+            Some(error) => Some(error),
+            None => None,
+        }
+    }
+}
+
+impl UnwindError {
+    fn new(error: imp::Error, frame: usize) -> UnwindError {
+        UnwindError {
+            message: error.to_string(),
+            frame,
+            error: Some(error),
+        }
+    }
+
+    /// Returns the message of the underlying error from the unwinding backend.
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the index of the frame at which unwinding stopped.
+    ///
+    /// This is the number of frames which were successfully unwound.
+    #[inline]
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+}
+
+/// The reason a thread's stack trace was cut short.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Truncation {
+    /// The trace reached the limit set by `TraceOptions::max_frames`.
+    MaxFrames,
+    /// Unwinding took longer than the limit set by `TraceOptions::unwind_timeout`.
+    Timeout,
+}
+
+/// A system call a thread was blocked in.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Syscall {
+    number: u64,
+    args: [u64; 6],
+}
+
+impl Syscall {
+    /// Returns the system call's number.
+    ///
+    /// System call numbers are architecture specific.
+    #[inline]
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    /// Returns the system call's name, if known.
+    ///
+    /// Names are only available on x86_64 and aarch64.
+    #[inline]
+    pub fn name(&self) -> Option<&'static str> {
+        syscall::name(self.number)
+    }
+
+    /// Returns the system call's arguments.
+    ///
+    /// Not all system calls take six arguments. The values of unused arguments are unspecified.
+    #[inline]
+    pub fn args(&self) -> &[u64; 6] {
+        &self.args
+    }
+}
+
+/// Information about a frame of a thread's kernel stack.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KernelFrame {
+    address: Option<u64>,
+    symbol: String,
+    offset: u64,
+    size: u64,
+    module: Option<String>,
+}
+
+impl KernelFrame {
+    /// Returns the address of the frame's instruction, if exposed by the kernel.
+    ///
+    /// The kernel only exposes addresses to sufficiently privileged processes.
+    #[inline]
+    pub fn address(&self) -> Option<u64> {
+        self.address
+    }
+
+    /// Returns the name of the kernel function.
+    #[inline]
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Returns the offset of the instruction from the function's starting address.
+    #[inline]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the size of the function.
+    #[inline]
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the name of the kernel module containing the function, if it is not part of the core kernel.
+    #[inline]
+    pub fn module(&self) -> Option<&str> {
+        self.module.as_deref()
+    }
+}
+
+/// The reason a thread's kernel stack could not be read.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KernelStackError {
+    /// The calling process lacks the `CAP_SYS_ADMIN` capability required to read kernel stacks.
+    PermissionDenied,
+    /// The kernel was built without support for exposing kernel stacks.
+    NotSupported,
+    /// Some other IO error occurred.
+    Io(#[cfg_attr(feature = "serde", serde(with = "schema::error_kind"))] io::ErrorKind),
+}
+
+impl fmt::Display for KernelStackError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            KernelStackError::PermissionDenied => {
+                fmt.write_str("reading kernel stacks requires CAP_SYS_ADMIN")
+            }
+            KernelStackError::NotSupported => {
+                fmt.write_str("the kernel does not support reading kernel stacks")
+            }
+            KernelStackError::Io(kind) => write!(fmt, "error reading kernel stack: {:?}", kind),
+        }
+    }
+}
+
+impl error::Error for KernelStackError {}
+
+/// Information about the scheduler state of a thread.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SchedInfo {
+    state: ThreadState,
+    wchan: Option<String>,
+    cpu: Option<u32>,
+    utime: Duration,
+    stime: Duration,
+    voluntary_context_switches: Option<u64>,
+    nonvoluntary_context_switches: Option<u64>,
+}
+
+impl SchedInfo {
+    /// Returns the state of the thread.
+    #[inline]
+    pub fn state(&self) -> ThreadState {
+        self.state
+    }
+
+    /// Returns the name of the kernel function the thread is blocked in, if known.
+    ///
+    /// The kernel only exposes this to sufficiently privileged processes.
+    #[inline]
+    pub fn wchan(&self) -> Option<&str> {
+        self.wchan.as_deref()
+    }
+
+    /// Returns the CPU the thread last ran on, if known.
+    #[inline]
+    pub fn cpu(&self) -> Option<u32> {
+        self.cpu
+    }
+
+    /// Returns the amount of time the thread has spent running in user mode.
+    #[inline]
+    pub fn utime(&self) -> Duration {
+        self.utime
+    }
+
+    /// Returns the amount of time the thread has spent running in kernel mode.
+    #[inline]
+    pub fn stime(&self) -> Duration {
+        self.stime
+    }
+
+    /// Returns the number of times the thread has voluntarily yielded the CPU, if known.
+    #[inline]
+    pub fn voluntary_context_switches(&self) -> Option<u64> {
+        self.voluntary_context_switches
+    }
+
+    /// Returns the number of times the thread has been preempted, if known.
+    #[inline]
+    pub fn nonvoluntary_context_switches(&self) -> Option<u64> {
+        self.nonvoluntary_context_switches
+    }
+}
+
+/// The scheduler state of a thread.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ThreadState {
+    /// Running or runnable.
+    Running,
+    /// Sleeping in an interruptible wait.
+    Sleeping,
+    /// Waiting in an uninterruptible sleep, typically on disk IO.
+    DiskSleep,
+    /// Stopped by a job control signal.
+    Stopped,
+    /// Stopped by a debugger.
+    TracingStop,
+    /// Exited but not yet reaped by its parent.
+    Zombie,
+    /// Dead.
+    Dead,
+    /// An idle kernel thread.
+    Idle,
+    /// A parked kernel thread.
+    Parked,
+    /// A state not known to this library, identified by the kernel's single character code.
+    Other(char),
+}
+
+impl ThreadState {
+    fn from_char(c: char) -> ThreadState {
+        match c {
+            'R' => ThreadState::Running,
+            'S' => ThreadState::Sleeping,
+            'D' => ThreadState::DiskSleep,
+            'T' => ThreadState::Stopped,
+            't' => ThreadState::TracingStop,
+            'Z' => ThreadState::Zombie,
+            'X' | 'x' => ThreadState::Dead,
+            'I' => ThreadState::Idle,
+            'P' => ThreadState::Parked,
+            c => ThreadState::Other(c),
+        }
+    }
+}
+
 /// Information about a stack frame of a remote process.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Frame {
     ip: u64,
     is_signal: bool,
     symbol: Option<Symbol>,
+    location: Option<Location>,
+    inlined_frames: Vec<InlinedFrame>,
+    registers: Option<Registers>,
 }
 
 impl Frame {
@@ -151,12 +573,64 @@ impl Frame {
     pub fn symbol(&self) -> Option<&Symbol> {
         self.symbol.as_ref()
     }
+
+    /// Returns the source location corresponding to this frame's instruction pointer, if known.
+    #[inline]
+    pub fn location(&self) -> Option<&Location> {
+        self.location.as_ref()
+    }
+
+    /// Returns the function calls which were inlined at this frame's instruction pointer, innermost first.
+    ///
+    /// The frame's symbol corresponds to the outermost function, into which all of these calls were inlined.
+    #[inline]
+    pub fn inlined_frames(&self) -> &[InlinedFrame] {
+        &self.inlined_frames
+    }
+
+    /// Returns the values of registers at this frame, if recorded.
+    #[inline]
+    pub fn registers(&self) -> Option<&Registers> {
+        self.registers.as_ref()
+    }
+}
+
+/// Information about a function call which was inlined into a stack frame.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InlinedFrame {
+    name: Option<String>,
+    demangled_name: Option<String>,
+    call_location: Option<Location>,
+}
+
+impl InlinedFrame {
+    /// Returns the name of the inlined function, if known.
+    #[inline]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the demangled name of the inlined function, if demangling was enabled and the name is a mangled Rust
+    /// or C++ symbol.
+    #[inline]
+    pub fn demangled_name(&self) -> Option<&str> {
+        self.demangled_name.as_deref()
+    }
+
+    /// Returns the source location of the call site, in the calling function, where the function was inlined.
+    #[inline]
+    pub fn call_location(&self) -> Option<&Location> {
+        self.call_location.as_ref()
+    }
 }
 
 /// Information about the symbol corresponding to a stack frame.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Symbol {
     name: String,
+    demangled_name: Option<String>,
     offset: u64,
     address: u64,
     size: u64,
@@ -169,6 +643,13 @@ impl Symbol {
         &self.name
     }
 
+    /// Returns the demangled name of the procedure, if demangling was enabled and the name is a mangled Rust or C++
+    /// symbol.
+    #[inline]
+    pub fn demangled_name(&self) -> Option<&str> {
+        self.demangled_name.as_deref()
+    }
+
     /// Returns the offset of the instruction pointer from the symbol's starting address.
     #[inline]
     pub fn offset(&self) -> u64 {
@@ -188,6 +669,90 @@ impl Symbol {
     }
 }
 
+/// Information about the source location corresponding to a stack frame.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Location {
+    file: PathBuf,
+    line: Option<u32>,
+    column: Option<u32>,
+}
+
+impl Location {
+    /// Returns the path of the source file.
+    #[inline]
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    /// Returns the line number, if known.
+    #[inline]
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// Returns the column number, if known.
+    #[inline]
+    pub fn column(&self) -> Option<u32> {
+        self.column
+    }
+}
+
+/// The values of registers at a stack frame.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Registers {
+    sp: Option<u64>,
+    cfa: Option<u64>,
+    callee_saved: Vec<Register>,
+}
+
+impl Registers {
+    /// Returns the value of the stack pointer, if known.
+    #[inline]
+    pub fn sp(&self) -> Option<u64> {
+        self.sp
+    }
+
+    /// Returns the canonical frame address, if known.
+    ///
+    /// This is the value of the stack pointer in the calling frame at the point of the call, so the difference between
+    /// it and the stack pointer is the size of the frame. Unless the unwinder reports it directly, which libunwind only
+    /// does on x86_64, it's taken from the stack pointer of the calling frame and is unknown for the outermost frame.
+    #[inline]
+    pub fn cfa(&self) -> Option<u64> {
+        self.cfa
+    }
+
+    /// Returns the values of the architecture's callee-saved registers which could be recovered at this frame.
+    #[inline]
+    pub fn callee_saved(&self) -> &[Register] {
+        &self.callee_saved
+    }
+}
+
+/// The value of a processor register.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Register {
+    name: Cow<'static, str>,
+    value: u64,
+}
+
+impl Register {
+    /// Returns the name of the register.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the value of the register.
+    #[inline]
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+}
+
 /// A convenience wrapper over `TraceOptions` which returns a maximally verbose trace.
 pub fn trace(pid: u32) -> Result<Process> {
     TraceOptions::new()
@@ -196,13 +761,35 @@ pub fn trace(pid: u32) -> Result<Process> {
         .trace(pid)
 }
 
+/// A convenience wrapper over `TraceOptions` which returns a maximally verbose trace of an ELF core dump.
+///
+/// This is only supported by the `dw` backend.
+#[cfg(feature = "dw")]
+pub fn trace_core(core: &Path, executable: Option<&Path>) -> Result<Process> {
+    TraceOptions::new()
+        .symbols(true)
+        .trace_core(core, executable)
+}
+
 /// Options controlling the behavior of tracing.
 #[derive(Debug, Clone)]
 pub struct TraceOptions {
     snapshot: bool,
     thread_names: bool,
+    sched_info: bool,
+    kernel_stacks: bool,
+    blocking_info: bool,
     symbols: bool,
+    source_lines: bool,
+    inlined_frames: bool,
+    demangle: bool,
+    strip_hashes: bool,
+    registers: bool,
     ptrace_attach: bool,
+    threads: Option<Vec<u32>>,
+    thread_name_pattern: Option<String>,
+    max_frames: Option<usize>,
+    unwind_timeout: Option<Duration>,
 }
 
 impl Default for TraceOptions {
@@ -210,8 +797,20 @@ impl Default for TraceOptions {
         TraceOptions {
             snapshot: false,
             thread_names: false,
+            sched_info: false,
+            kernel_stacks: false,
+            blocking_info: false,
             symbols: false,
+            source_lines: false,
+            inlined_frames: false,
+            demangle: false,
+            strip_hashes: false,
+            registers: false,
             ptrace_attach: true,
+            threads: None,
+            thread_name_pattern: None,
+            max_frames: None,
+            unwind_timeout: None,
         }
     }
 }
@@ -241,6 +840,39 @@ impl TraceOptions {
         self
     }
 
+    /// If set, the scheduler state of the process's threads will be recorded.
+    ///
+    /// The state is read before each thread is stopped for tracing.
+    ///
+    /// Defaults to `false`.
+    pub fn sched_info(&mut self, sched_info: bool) -> &mut TraceOptions {
+        self.sched_info = sched_info;
+        self
+    }
+
+    /// If set, the kernel stacks of the process's threads will be recorded.
+    ///
+    /// Like the scheduler state, the kernel stack is read before each thread is stopped for tracing. Reading kernel
+    /// stacks requires the `CAP_SYS_ADMIN` capability.
+    ///
+    /// Defaults to `false`.
+    pub fn kernel_stacks(&mut self, kernel_stacks: bool) -> &mut TraceOptions {
+        self.kernel_stacks = kernel_stacks;
+        self
+    }
+
+    /// If set, the number and arguments of the system call each thread was blocked in will be recorded.
+    ///
+    /// The syscall is read from the thread's registers once it has been stopped, falling back to
+    /// `/proc/<pid>/task/<tid>/syscall` on other architectures. It's used to determine the futex each thread was
+    /// waiting on.
+    ///
+    /// Defaults to `false`.
+    pub fn blocking_info(&mut self, blocking_info: bool) -> &mut TraceOptions {
+        self.blocking_info = blocking_info;
+        self
+    }
+
     /// If set, information about the symbol at each frame will be recorded.
     ///
     /// Defaults to `false`.
@@ -249,6 +881,60 @@ impl TraceOptions {
         self
     }
 
+    /// If set, the source file and line of each frame will be recorded.
+    ///
+    /// This requires that debug information be available for the process's binaries.
+    ///
+    /// Defaults to `false`.
+    pub fn source_lines(&mut self, source_lines: bool) -> &mut TraceOptions {
+        self.source_lines = source_lines;
+        self
+    }
+
+    /// If set, the function calls inlined at each frame will be recorded.
+    ///
+    /// This requires that debug information be available for the process's binaries.
+    ///
+    /// Defaults to `false`.
+    pub fn inlined_frames(&mut self, inlined_frames: bool) -> &mut TraceOptions {
+        self.inlined_frames = inlined_frames;
+        self
+    }
+
+    /// If set, Rust and C++ symbol names will be demangled.
+    ///
+    /// The demangled names are available via [`Symbol::demangled_name`] and [`InlinedFrame::demangled_name`].
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`Symbol::demangled_name`]: struct.Symbol.html#method.demangled_name
+    /// [`InlinedFrame::demangled_name`]: struct.InlinedFrame.html#method.demangled_name
+    pub fn demangle(&mut self, demangle: bool) -> &mut TraceOptions {
+        self.demangle = demangle;
+        self
+    }
+
+    /// If set, the trailing hash will be removed from demangled Rust symbol names.
+    ///
+    /// For example, `foo::bar::h0123456789abcdef` will be demangled as `foo::bar`.
+    ///
+    /// Defaults to `false`.
+    pub fn strip_hashes(&mut self, strip_hashes: bool) -> &mut TraceOptions {
+        self.strip_hashes = strip_hashes;
+        self
+    }
+
+    /// If set, the stack pointer, canonical frame address, and callee-saved registers of each frame will be recorded.
+    ///
+    /// Only x86_64 and aarch64 callee-saved registers are currently supported. The `dw` backend requires elfutils
+    /// 0.188 or newer to read registers, and records none with older versions.
+    ///
+    /// Defaults to `false`.
+    pub fn registers(&mut self, registers: bool) -> &mut TraceOptions {
+        self.registers = registers;
+        self
+    }
+
     /// If set, `rstack` will automatically attach to threads via ptrace.
     ///
     /// If disabled, the calling process must already be attached to all traced threads, and the
@@ -260,38 +946,134 @@ impl TraceOptions {
         self
     }
 
+    /// If set, only the threads with the specified IDs will be traced.
+    ///
+    /// Other threads are never stopped, which reduces the impact of tracing on a process with many threads. In
+    /// snapshot mode, the snapshot is only consistent among the traced threads.
+    ///
+    /// Defaults to tracing all threads.
+    pub fn threads(&mut self, threads: &[u32]) -> &mut TraceOptions {
+        self.threads = Some(threads.to_vec());
+        self
+    }
+
+    /// If set, only the threads whose names match the specified pattern will be traced.
+    ///
+    /// The pattern is matched against the entire thread name as reported by `/proc/<pid>/task/<tid>/comm`. A `*`
+    /// matches any sequence of characters and a `?` matches any single character. Threads are matched before they are
+    /// stopped, and threads which don't match are never stopped. If combined with `threads`, a thread must satisfy
+    /// both.
+    ///
+    /// Defaults to tracing all threads.
+    pub fn thread_name_pattern(&mut self, pattern: &str) -> &mut TraceOptions {
+        self.thread_name_pattern = Some(pattern.to_string());
+        self
+    }
+
+    /// If set, unwinding of each thread will stop once this many frames have been recorded.
+    ///
+    /// A corrupted stack can otherwise send the unwinder around in circles for a very long time while the thread is
+    /// stopped. Truncated traces are reported by `Thread::truncation`.
+    ///
+    /// Defaults to no limit.
+    pub fn max_frames(&mut self, max_frames: usize) -> &mut TraceOptions {
+        self.max_frames = Some(max_frames);
+        self
+    }
+
+    /// If set, unwinding of each thread will stop once it has taken this long.
+    ///
+    /// The limit is checked after each frame, so the time spent unwinding a thread may slightly exceed it. Truncated
+    /// traces are reported by `Thread::truncation`.
+    ///
+    /// Defaults to no limit.
+    pub fn unwind_timeout(&mut self, unwind_timeout: Duration) -> &mut TraceOptions {
+        self.unwind_timeout = Some(unwind_timeout);
+        self
+    }
+
     /// Traces the threads of the specified process.
     pub fn trace(&self, pid: u32) -> Result<Process> {
         let mut state = imp::State::new(pid).map_err(|e| Error(ErrorInner::Unwind(e)))?;
+        self.trace_with_state(pid, &mut state)
+    }
 
-        let threads = if self.snapshot {
-            self.trace_snapshot(pid, &mut state)?
+    fn trace_with_state(&self, pid: u32, state: &mut imp::State) -> Result<Process> {
+        let (threads, snapshot_converged) = if self.snapshot {
+            let (threads, converged) = self.trace_snapshot(pid, state)?;
+            (threads, Some(converged))
         } else {
-            self.trace_rolling(pid, &mut state)?
+            (self.trace_rolling(pid, state)?, None)
         };
 
-        Ok(Process { id: pid, threads })
+        Ok(Process {
+            id: pid,
+            threads,
+            snapshot_converged,
+        })
+    }
+
+    /// Traces the threads of the process which produced the specified ELF core dump.
+    ///
+    /// If the path to the process's executable is not provided, it will be located via information in the core dump.
+    /// Thread names are not recorded in core dumps, so the `thread_names` option has no effect.
+    ///
+    /// This is only supported by the `dw` backend.
+    #[cfg(feature = "dw")]
+    pub fn trace_core(&self, core: &Path, executable: Option<&Path>) -> Result<Process> {
+        imp::trace_core(core, executable, self)
     }
 
-    fn trace_snapshot(&self, pid: u32, state: &mut imp::State) -> Result<Vec<Thread>> {
-        let threads = snapshot_threads(pid, self.ptrace_attach)?
-            .iter()
-            .map(|t| t.info(pid, state, self))
-            .collect();
+    fn trace_snapshot(&self, pid: u32, state: &mut imp::State) -> Result<(Vec<Thread>, bool)> {
+        let (threads, converged) = snapshot_threads(pid, self)?;
+        let threads = threads.iter().map(|t| t.info(pid, state, self)).collect();
 
-        Ok(threads)
+        Ok((threads, converged))
+    }
+
+    // determines if unwinding should stop after the latest frame
+    fn truncation(&self, frames: usize, start: Instant) -> Option<Truncation> {
+        match (self.max_frames, self.unwind_timeout) {
+            (Some(max), _) if frames >= max => Some(Truncation::MaxFrames),
+            (_, Some(timeout)) if start.elapsed() >= timeout => Some(Truncation::Timeout),
+            _ => None,
+        }
+    }
+
+    fn demangle_frames(&self, frames: &mut [Frame]) {
+        if !self.demangle {
+            return;
+        }
+
+        for frame in frames {
+            if let Some(symbol) = &mut frame.symbol {
+                symbol.demangled_name = demangle(&symbol.name, self.strip_hashes);
+            }
+            for inlined in &mut frame.inlined_frames {
+                inlined.demangled_name = inlined
+                    .name
+                    .as_ref()
+                    .and_then(|n| demangle(n, self.strip_hashes));
+            }
+        }
     }
 
     fn trace_rolling(&self, pid: u32, state: &mut imp::State) -> Result<Vec<Thread>> {
         let mut threads = vec![];
 
         each_thread(pid, |tid| {
+            if !self.selects(pid, tid) {
+                return Ok(());
+            }
+
+            let unattached = self.read_unattached(pid, tid);
+
             let thread = if self.ptrace_attach {
                 TracedThread::attach(tid)
             } else {
                 TracedThread::traced(tid)
             };
-            let thread = match thread {
+            let mut thread = match thread {
                 Ok(thread) => thread,
                 Err(ref e) if e.raw_os_error() == Some(ESRCH) => {
                     debug!("error attaching to thread {}: {}", tid, e);
@@ -299,6 +1081,7 @@ impl TraceOptions {
                 }
                 Err(e) => return Err(Error(ErrorInner::Io(e))),
             };
+            thread.unattached = unattached;
 
             let trace = thread.info(pid, state, self);
             threads.push(trace);
@@ -307,49 +1090,124 @@ impl TraceOptions {
 
         Ok(threads)
     }
+
+    fn selects(&self, pid: u32, tid: u32) -> bool {
+        if let Some(threads) = &self.threads {
+            if !threads.contains(&tid) {
+                return false;
+            }
+        }
+
+        match &self.thread_name_pattern {
+            Some(pattern) => match proc::thread_name(pid, tid) {
+                Ok(name) => glob_matches(pattern, &name),
+                Err(e) => {
+                    debug!("error getting name for thread {}: {}", tid, e);
+                    false
+                }
+            },
+            None => true,
+        }
+    }
+
+    fn read_unattached(&self, pid: u32, tid: u32) -> UnattachedInfo {
+        let sched_info = if self.sched_info {
+            match proc::sched_info(pid, tid) {
+                Ok(info) => Some(info),
+                Err(e) => {
+                    debug!("error getting scheduler state for thread {}: {}", tid, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let kernel_stack = if self.kernel_stacks {
+            Some(proc::kernel_stack(pid, tid))
+        } else {
+            None
+        };
+
+        let syscall = if self.blocking_info {
+            match proc::syscall(pid, tid) {
+                Ok(syscall) => syscall,
+                Err(e) => {
+                    debug!("error getting syscall for thread {}: {}", tid, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        UnattachedInfo {
+            sched_info,
+            kernel_stack,
+            syscall,
+        }
+    }
 }
 
-fn snapshot_threads(pid: u32, ptrace_attach: bool) -> Result<BTreeSet<TracedThread>> {
+// the maximum number of passes over /proc/<pid>/task made while stopping the threads of a process
+const MAX_SNAPSHOT_SCANS: usize = 100;
+
+fn snapshot_threads(pid: u32, options: &TraceOptions) -> Result<(BTreeSet<TracedThread>, bool)> {
     let mut threads = BTreeSet::new();
 
-    // new threads may be created while we're in the process of stopping them all, so loop a couple
-    // of times to hopefully converge
-    for _ in 0..5 {
-        let prev = threads.len();
-        add_threads(&mut threads, pid, ptrace_attach)?;
-        if prev == threads.len() {
-            break;
+    // Threads created by a thread we've already attached to are caught via PTRACE_O_TRACECLONE, so new threads can
+    // only come from ones we haven't stopped yet. Once a scan finds no new threads, every thread has been stopped.
+    for _ in 0..MAX_SNAPSHOT_SCANS {
+        if !add_threads(&mut threads, pid, options)? {
+            return Ok((threads, true));
         }
     }
 
-    Ok(threads)
+    Ok((threads, false))
 }
 
-fn add_threads(threads: &mut BTreeSet<TracedThread>, pid: u32, ptrace_attach: bool) -> Result<()> {
+// returns true if any new threads were found
+fn add_threads(
+    threads: &mut BTreeSet<TracedThread>,
+    pid: u32,
+    options: &TraceOptions,
+) -> Result<bool> {
+    let mut added = false;
+
     each_thread(pid, |tid| {
-        if !threads.contains(&tid) {
-            let thread = if ptrace_attach {
-                TracedThread::attach(pid)
+        if !threads.contains(&tid) && options.selects(pid, tid) {
+            let unattached = options.read_unattached(pid, tid);
+
+            let thread = if options.ptrace_attach {
+                TracedThread::attach_tracing_clones(tid)
             } else {
-                TracedThread::traced(pid)
+                TracedThread::traced(tid).map(|t| (t, None))
             };
-            let thread = match thread {
+            let (mut thread, clone) = match thread {
                 Ok(thread) => thread,
                 // ESRCH just means the thread died in the middle of things, which is fine
                 Err(e) => {
                     if e.raw_os_error() == Some(ESRCH) {
-                        debug!("error attaching to thread {}: {}", pid, e);
+                        debug!("error attaching to thread {}: {}", tid, e);
                         return Ok(());
                     } else {
                         return Err(Error(ErrorInner::Io(e)));
                     }
                 }
             };
+            thread.unattached = unattached;
             threads.insert(thread);
+            // an unselected clone is detached when dropped
+            if let Some(clone) = clone.filter(|c| options.selects(pid, c.id)) {
+                threads.insert(clone);
+            }
+            added = true;
         }
 
         Ok(())
-    })
+    })?;
+
+    Ok(added)
 }
 
 fn each_thread<F>(pid: u32, mut f: F) -> Result<()>
@@ -376,6 +1234,17 @@ struct TracedThread {
     // True if TraceOptions::ptrace_attach was true (default value)
     // It means that Drop should perform detach
     should_detach: bool,
+    // A signal which arrived while we were stopping the thread, to be redelivered when detaching
+    pending_signal: c_int,
+    unattached: UnattachedInfo,
+}
+
+// Information which has to be read before attaching to a thread, since stopping it changes its state
+#[derive(Default)]
+struct UnattachedInfo {
+    sched_info: Option<SchedInfo>,
+    kernel_stack: Option<result::Result<Vec<KernelFrame>, KernelStackError>>,
+    syscall: Option<Syscall>,
 }
 
 impl Drop for TracedThread {
@@ -386,7 +1255,7 @@ impl Drop for TracedThread {
                     PTRACE_DETACH,
                     self.id as pid_t,
                     ptr::null_mut::<c_void>(),
-                    ptr::null_mut::<c_void>(),
+                    self.pending_signal as *mut c_void,
                 );
             }
         }
@@ -422,26 +1291,38 @@ impl Borrow<u32> for TracedThread {
 
 impl TracedThread {
     fn attach(pid: u32) -> io::Result<TracedThread> {
+        TracedThread::seize(pid, 0).map(|(thread, _)| thread)
+    }
+
+    // Like `attach`, but also automatically attaches to any threads the thread creates. If it created one while it was
+    // being stopped, that thread is returned as well.
+    fn attach_tracing_clones(pid: u32) -> io::Result<(TracedThread, Option<TracedThread>)> {
+        TracedThread::seize(pid, PTRACE_O_TRACECLONE)
+    }
+
+    fn seize(pid: u32, options: c_int) -> io::Result<(TracedThread, Option<TracedThread>)> {
         unsafe {
             let ret = ptrace(
                 PTRACE_SEIZE,
                 pid as pid_t,
                 ptr::null_mut::<c_void>(),
-                ptr::null_mut::<c_void>(),
+                options as *mut c_void,
             );
             if ret != 0 {
                 let e = io::Error::last_os_error();
                 // ptrace returns ESRCH if PTRACE_SEIZE isn't supported for some reason
                 if e.raw_os_error() == Some(ESRCH as i32) {
-                    return TracedThread::new_fallback(pid);
+                    return TracedThread::new_fallback(pid).map(|t| (t, None));
                 }
 
                 return Err(e);
             }
 
-            let thread = TracedThread {
+            let mut thread = TracedThread {
                 id: pid,
                 should_detach: true,
+                pending_signal: 0,
+                unattached: UnattachedInfo::default(),
             };
 
             let ret = ptrace(
@@ -454,22 +1335,41 @@ impl TracedThread {
                 return Err(io::Error::last_os_error());
             }
 
-            let mut status = 0;
-            while waitpid(pid as pid_t, &mut status, __WALL) < 0 {
-                let e = io::Error::last_os_error();
-                if e.kind() != io::ErrorKind::Interrupted {
-                    return Err(e);
-                }
-            }
+            let status = wait_stopped(pid)?;
+
+            // the thread may have stopped for some reason other than our interrupt, but either way it's stopped now
+            match status >> 16 {
+                PTRACE_EVENT_STOP => Ok((thread, None)),
+                PTRACE_EVENT_CLONE => {
+                    let mut tid: c_ulong = 0;
+                    let ret = ptrace(
+                        PTRACE_GETEVENTMSG,
+                        pid as pid_t,
+                        ptr::null_mut::<c_void>(),
+                        &mut tid as *mut c_ulong,
+                    );
+                    if ret != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
 
-            if !WIFSTOPPED(status) {
-                return Err(io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("unexpected wait status {}", status),
-                ));
-            }
+                    let clone = TracedThread {
+                        id: tid as u32,
+                        should_detach: true,
+                        pending_signal: 0,
+                        unattached: UnattachedInfo::default(),
+                    };
+                    // the new thread starts out in a ptrace stop
+                    wait_stopped(clone.id)?;
 
-            Ok(thread)
+                    Ok((thread, Some(clone)))
+                }
+                // a signal-delivery-stop, which would be suppressed by detaching if not redelivered
+                0 => {
+                    thread.pending_signal = WSTOPSIG(status);
+                    Ok((thread, None))
+                }
+                _ => Ok((thread, None)),
+            }
         }
     }
 
@@ -479,6 +1379,8 @@ impl TracedThread {
         Ok(TracedThread {
             id: pid,
             should_detach: false,
+            pending_signal: 0,
+            unattached: UnattachedInfo::default(),
         })
     }
 
@@ -497,6 +1399,8 @@ impl TracedThread {
             let thread = TracedThread {
                 id: pid,
                 should_detach: true,
+                pending_signal: 0,
+                unattached: UnattachedInfo::default(),
             };
 
             let mut status = 0;
@@ -543,30 +1447,58 @@ impl TracedThread {
             None
         };
 
-        let frames = self.dump(state, options);
+        let syscall = if options.blocking_info {
+            match syscall::read(self.id) {
+                Ok(Some(syscall)) => Some(syscall),
+                Ok(None) => self.unattached.syscall.clone(),
+                Err(e) => {
+                    debug!("error reading syscall of thread {}: {}", self.id, e);
+                    self.unattached.syscall.clone()
+                }
+            }
+        } else {
+            None
+        };
+
+        let (frames, truncation, error) = self.dump(state, options);
 
         Thread {
             id: self.id,
             name,
+            sched_info: self.unattached.sched_info.clone(),
+            kernel_stack: self.unattached.kernel_stack.clone(),
+            syscall,
+            truncation,
+            error,
             frames,
         }
     }
 
-    fn dump(&self, state: &mut imp::State, options: &TraceOptions) -> Vec<Frame> {
+    fn dump(
+        &self,
+        state: &mut imp::State,
+        options: &TraceOptions,
+    ) -> (Vec<Frame>, Option<Truncation>, Option<UnwindError>) {
         let mut frames = vec![];
 
-        if let Err(e) = self.dump_inner(state, options, &mut frames) {
-            debug!("error tracing thread {}: {}", self.id, e);
-        }
+        let (truncation, error) = match self.dump_inner(state, options, &mut frames) {
+            Ok(truncation) => (truncation, None),
+            Err(error) => {
+                debug!("error tracing thread {}: {}", self.id, error);
+                let error = UnwindError::new(error, frames.len());
+                (None, Some(error))
+            }
+        };
+
+        fill_cfas(&mut frames);
+        options.demangle_frames(&mut frames);
 
-        frames
+        (frames, truncation, error)
     }
 
     fn name(&self, pid: u32) -> Option<String> {
-        let path = format!("/proc/{}/task/{}/comm", pid, self.id);
-        let mut name = vec![];
-        match File::open(path).and_then(|mut f| f.read_to_end(&mut name)) {
-            Ok(_) => Some(String::from_utf8_lossy(&name).trim().to_string()),
+        match proc::thread_name(pid, self.id) {
+            Ok(name) => Some(name),
             Err(e) => {
                 debug!("error getting name for thread {}: {}", self.id, e);
                 None
@@ -574,3 +1506,127 @@ impl TracedThread {
         }
     }
 }
+
+// the CFA of a frame is the stack pointer of its caller at the point of the call, so it can be recovered for every
+// frame but the outermost when the unwinder doesn't report it directly
+fn fill_cfas(frames: &mut [Frame]) {
+    for i in 1..frames.len() {
+        let sp = frames[i].registers.as_ref().and_then(|r| r.sp);
+        if let Some(registers) = &mut frames[i - 1].registers {
+            if registers.cfa.is_none() {
+                registers.cfa = sp;
+            }
+        }
+    }
+}
+
+fn wait_stopped(pid: u32) -> io::Result<c_int> {
+    let mut status = 0;
+    while unsafe { waitpid(pid as pid_t, &mut status, __WALL) } < 0 {
+        let e = io::Error::last_os_error();
+        if e.kind() != io::ErrorKind::Interrupted {
+            return Err(e);
+        }
+    }
+
+    if !WIFSTOPPED(status) {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("unexpected wait status {}", status),
+        ));
+    }
+
+    Ok(status)
+}
+
+// matches an entire string against a pattern where `*` matches any sequence of characters and `?` any one character
+fn glob_matches(pattern: &str, s: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let s = s.chars().collect::<Vec<_>>();
+
+    let (mut p, mut i) = (0, 0);
+    // the position of the last `*` in the pattern, and the position in the string it's currently matched up to
+    let mut backtrack = None;
+    while i < s.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, i));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == s[i] => {
+                p += 1;
+                i += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    i = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn demangle(name: &str, strip_hashes: bool) -> Option<String> {
+    if let Ok(name) = rustc_demangle::try_demangle(name) {
+        return if strip_hashes {
+            Some(format!("{:#}", name))
+        } else {
+            Some(name.to_string())
+        };
+    }
+
+    cpp_demangle::Symbol::new(name)
+        .ok()
+        .and_then(|s| s.demangle(&Default::default()).ok())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn glob_literal() {
+        assert!(glob_matches("worker", "worker"));
+        assert!(!glob_matches("worker", "worker-1"));
+        assert!(!glob_matches("worker-1", "worker"));
+        assert!(glob_matches("", ""));
+        assert!(!glob_matches("", "worker"));
+    }
+
+    #[test]
+    fn glob_question_mark() {
+        assert!(glob_matches("worker-?", "worker-1"));
+        assert!(!glob_matches("worker-?", "worker-"));
+        assert!(!glob_matches("worker-?", "worker-12"));
+    }
+
+    #[test]
+    fn glob_star() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("*", "anything"));
+        assert!(glob_matches("tokio-*", "tokio-runtime-worker"));
+        assert!(glob_matches("*-worker", "tokio-runtime-worker"));
+        assert!(glob_matches("t*o*r", "tokio-runtime-worker"));
+        assert!(!glob_matches("tokio-*", "rayon-worker"));
+        assert!(!glob_matches("*-pool", "tokio-runtime-worker"));
+    }
+
+    #[test]
+    fn glob_star_backtracks() {
+        // the first `-` after the star is a false start
+        assert!(glob_matches("*-worker-?", "pool-a-worker-1"));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(!glob_matches("a*b*c", "aXbYbZ"));
+    }
+
+    #[test]
+    fn glob_multibyte() {
+        assert!(glob_matches("wörk?r", "wörker"));
+        assert!(glob_matches("?", "ö"));
+    }
+}
@@ -0,0 +1,383 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::debug;
+use prost::Message;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{imp, Frame, Location, Process, Profile};
+
+/// A file-backed executable memory mapping of a process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Map {
+    pub start: u64,
+    pub end: u64,
+    pub offset: u64,
+    pub path: PathBuf,
+}
+
+/// An exporter which writes stack traces as a gzip-compressed pprof [`profile.proto`].
+///
+/// Each unique stack becomes a sample labeled with its thread's name under the `thread` key. Memory mappings and the
+/// build IDs of their backing files are read from the traced process when its stacks are added, so the process must
+/// still be running at that point.
+///
+/// Requires the `pprof` Cargo feature.
+///
+/// [`profile.proto`]: https://github.com/google/pprof/blob/main/proto/profile.proto
+#[derive(Debug, Default)]
+pub struct Pprof {
+    time_nanos: i64,
+    sample_type: Vec<proto::ValueType>,
+    strings: Vec<String>,
+    string_ids: HashMap<String, i64>,
+    samples: Vec<proto::Sample>,
+    mappings: Vec<proto::Mapping>,
+    map_pids: Vec<u32>,
+    locations: Vec<proto::Location>,
+    location_ids: HashMap<u64, u64>,
+    functions: Vec<proto::Function>,
+    function_ids: HashMap<(i64, i64), u64>,
+}
+
+impl Pprof {
+    /// Returns a new, empty `Pprof`.
+    pub fn new() -> Pprof {
+        let mut pprof = Pprof::default();
+        // the first entry of the string table must be the empty string
+        pprof.string("");
+        pprof.sample_type = vec![proto::ValueType {
+            r#type: pprof.string("samples"),
+            unit: pprof.string("count"),
+        }];
+        pprof.time_nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as i64);
+        pprof
+    }
+
+    /// Adds the stacks of the threads of a traced process, each with a count of 1.
+    pub fn add_process(&mut self, process: &Process) -> &mut Pprof {
+        self.add_mappings(process.id());
+        for thread in process.threads() {
+            self.add_stack(thread.name(), thread.frames(), 1);
+        }
+        self
+    }
+
+    /// Adds the stacks of a sampled process, each with the number of times it was sampled.
+    pub fn add_profile(&mut self, profile: &Profile) -> &mut Pprof {
+        self.add_mappings(profile.id());
+        for stack in profile.stacks() {
+            self.add_stack(stack.thread_name(), stack.frames(), stack.count());
+        }
+        self
+    }
+
+    /// Adds the executable memory mappings of a running process, along with the build IDs of their backing files.
+    ///
+    /// This is done automatically by `add_process` and `add_profile`. Mappings should be added before the stacks
+    /// which reference them.
+    pub fn add_mappings(&mut self, pid: u32) -> &mut Pprof {
+        if self.map_pids.contains(&pid) {
+            return self;
+        }
+        self.map_pids.push(pid);
+
+        let maps = match imp::executable_maps(pid) {
+            Ok(maps) => maps,
+            Err(e) => {
+                debug!("error reading maps of process {}: {}", pid, e);
+                return self;
+            }
+        };
+        let build_ids = imp::build_ids(pid, &maps);
+
+        for (map, build_id) in maps.iter().zip(build_ids) {
+            self.add_mapping(map, build_id.as_deref());
+        }
+        self
+    }
+
+    fn add_mapping(&mut self, map: &Map, build_id: Option<&[u8]>) {
+        let filename = self.string(&map.path.to_string_lossy());
+        let build_id = match build_id {
+            Some(build_id) => {
+                let mut hex = String::with_capacity(build_id.len() * 2);
+                for b in build_id {
+                    let _ = write!(hex, "{:02x}", b);
+                }
+                self.string(&hex)
+            }
+            None => 0,
+        };
+
+        let duplicate = self.mappings.iter().any(|m| {
+            m.memory_start == map.start
+                && m.memory_limit == map.end
+                && m.file_offset == map.offset
+                && m.filename == filename
+        });
+        if duplicate {
+            return;
+        }
+
+        self.mappings.push(proto::Mapping {
+            id: self.mappings.len() as u64 + 1,
+            memory_start: map.start,
+            memory_limit: map.end,
+            file_offset: map.offset,
+            filename,
+            build_id,
+            ..proto::Mapping::default()
+        });
+    }
+
+    /// Adds a single stack with the specified count.
+    ///
+    /// The frames are ordered innermost first, as returned by `Thread::frames`. Frames are only associated with
+    /// mappings which were previously added with `add_mappings`.
+    pub fn add_stack(
+        &mut self,
+        thread_name: Option<&str>,
+        frames: &[Frame],
+        count: u64,
+    ) -> &mut Pprof {
+        let location_id = frames.iter().map(|f| self.location(f)).collect();
+
+        let mut label = vec![];
+        if let Some(thread_name) = thread_name {
+            label.push(proto::Label {
+                key: self.string("thread"),
+                str: self.string(thread_name),
+                ..proto::Label::default()
+            });
+        }
+
+        self.samples.push(proto::Sample {
+            location_id,
+            value: vec![count as i64],
+            label,
+        });
+        self
+    }
+
+    fn location(&mut self, frame: &Frame) -> u64 {
+        if let Some(&id) = self.location_ids.get(&frame.ip()) {
+            return id;
+        }
+
+        // lines are ordered innermost first, and each inlined function was called from the location of the one
+        // after it
+        let mut line = vec![];
+        let mut location = frame.location();
+        for inlined in frame.inlined_frames() {
+            let name = inlined.demangled_name().or_else(|| inlined.name());
+            line.push(self.line(name, inlined.name(), location));
+            location = inlined.call_location();
+        }
+        if let Some(symbol) = frame.symbol() {
+            let name = symbol.demangled_name().unwrap_or_else(|| symbol.name());
+            line.push(self.line(Some(name), Some(symbol.name()), location));
+        }
+
+        let mapping_id = self
+            .mappings
+            .iter()
+            .find(|m| m.memory_start <= frame.ip() && frame.ip() < m.memory_limit)
+            .map_or(0, |m| m.id);
+
+        let id = self.locations.len() as u64 + 1;
+        self.locations.push(proto::Location {
+            id,
+            mapping_id,
+            address: frame.ip(),
+            line,
+            is_folded: false,
+        });
+        self.location_ids.insert(frame.ip(), id);
+        id
+    }
+
+    fn line(
+        &mut self,
+        name: Option<&str>,
+        system_name: Option<&str>,
+        location: Option<&Location>,
+    ) -> proto::Line {
+        let name = self.string(name.unwrap_or("[unknown]"));
+        let filename = match location {
+            Some(location) => self.string(&location.file().to_string_lossy()),
+            None => 0,
+        };
+
+        let next_id = self.functions.len() as u64 + 1;
+        let function_id = *self.function_ids.entry((name, filename)).or_insert(next_id);
+        if function_id == next_id {
+            let system_name = match system_name {
+                Some(system_name) => self.string(system_name),
+                None => name,
+            };
+            self.functions.push(proto::Function {
+                id: function_id,
+                name,
+                system_name,
+                filename,
+                start_line: 0,
+            });
+        }
+
+        proto::Line {
+            function_id,
+            line: location.and_then(|l| l.line()).map_or(0, i64::from),
+        }
+    }
+
+    fn string(&mut self, s: &str) -> i64 {
+        if let Some(&id) = self.string_ids.get(s) {
+            return id;
+        }
+
+        let id = self.strings.len() as i64;
+        self.strings.push(s.to_string());
+        self.string_ids.insert(s.to_string(), id);
+        id
+    }
+
+    /// Writes the profile as a gzip-compressed protobuf message.
+    pub fn write<W>(&self, w: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let profile = proto::Profile {
+            sample_type: self.sample_type.clone(),
+            sample: self.samples.clone(),
+            mapping: self.mappings.clone(),
+            location: self.locations.clone(),
+            function: self.functions.clone(),
+            string_table: self.strings.clone(),
+            time_nanos: self.time_nanos,
+        };
+
+        let mut w = GzEncoder::new(w, Compression::default());
+        w.write_all(&profile.encode_to_vec())?;
+        w.finish()?;
+        Ok(())
+    }
+}
+
+// A subset of https://github.com/google/pprof/blob/main/proto/profile.proto
+mod proto {
+    use prost::Message;
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Profile {
+        #[prost(message, repeated, tag = "1")]
+        pub sample_type: Vec<ValueType>,
+        #[prost(message, repeated, tag = "2")]
+        pub sample: Vec<Sample>,
+        #[prost(message, repeated, tag = "3")]
+        pub mapping: Vec<Mapping>,
+        #[prost(message, repeated, tag = "4")]
+        pub location: Vec<Location>,
+        #[prost(message, repeated, tag = "5")]
+        pub function: Vec<Function>,
+        #[prost(string, repeated, tag = "6")]
+        pub string_table: Vec<String>,
+        #[prost(int64, tag = "9")]
+        pub time_nanos: i64,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct ValueType {
+        #[prost(int64, tag = "1")]
+        pub r#type: i64,
+        #[prost(int64, tag = "2")]
+        pub unit: i64,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Sample {
+        #[prost(uint64, repeated, tag = "1")]
+        pub location_id: Vec<u64>,
+        #[prost(int64, repeated, tag = "2")]
+        pub value: Vec<i64>,
+        #[prost(message, repeated, tag = "3")]
+        pub label: Vec<Label>,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Label {
+        #[prost(int64, tag = "1")]
+        pub key: i64,
+        #[prost(int64, tag = "2")]
+        pub str: i64,
+        #[prost(int64, tag = "3")]
+        pub num: i64,
+        #[prost(int64, tag = "4")]
+        pub num_unit: i64,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Mapping {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        #[prost(uint64, tag = "2")]
+        pub memory_start: u64,
+        #[prost(uint64, tag = "3")]
+        pub memory_limit: u64,
+        #[prost(uint64, tag = "4")]
+        pub file_offset: u64,
+        #[prost(int64, tag = "5")]
+        pub filename: i64,
+        #[prost(int64, tag = "6")]
+        pub build_id: i64,
+        #[prost(bool, tag = "7")]
+        pub has_functions: bool,
+        #[prost(bool, tag = "8")]
+        pub has_filenames: bool,
+        #[prost(bool, tag = "9")]
+        pub has_line_numbers: bool,
+        #[prost(bool, tag = "10")]
+        pub has_inline_frames: bool,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Location {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        #[prost(uint64, tag = "2")]
+        pub mapping_id: u64,
+        #[prost(uint64, tag = "3")]
+        pub address: u64,
+        #[prost(message, repeated, tag = "4")]
+        pub line: Vec<Line>,
+        #[prost(bool, tag = "5")]
+        pub is_folded: bool,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Line {
+        #[prost(uint64, tag = "1")]
+        pub function_id: u64,
+        #[prost(int64, tag = "2")]
+        pub line: i64,
+    }
+
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Function {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        #[prost(int64, tag = "2")]
+        pub name: i64,
+        #[prost(int64, tag = "3")]
+        pub system_name: i64,
+        #[prost(int64, tag = "4")]
+        pub filename: i64,
+        #[prost(int64, tag = "5")]
+        pub start_line: i64,
+    }
+}
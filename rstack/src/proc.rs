@@ -0,0 +1,273 @@
+//! Parsers for per-process and per-thread information exposed by the `/proc` pseudo-filesystem.
+use libc::{sysconf, _SC_CLK_TCK, EACCES, EPERM};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::result;
+use std::time::Duration;
+
+use crate::{KernelFrame, KernelStackError, SchedInfo, Syscall, ThreadState};
+
+/// Reads the name of a thread.
+pub fn thread_name(pid: u32, tid: u32) -> io::Result<String> {
+    let name = fs::read(format!("/proc/{}/task/{}/comm", pid, tid))?;
+    Ok(String::from_utf8_lossy(&name).trim().to_string())
+}
+
+/// Reads the scheduler state of a thread.
+///
+/// This must be called before the thread is attached to, since a ptrace stop changes its state.
+pub fn sched_info(pid: u32, tid: u32) -> io::Result<SchedInfo> {
+    let dir = format!("/proc/{}/task/{}", pid, tid);
+
+    let stat = fs::read_to_string(format!("{}/stat", dir))?;
+    let stat = parse_stat(&stat)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed stat file"))?;
+
+    let status = fs::read_to_string(format!("{}/status", dir))?;
+
+    // wchan reads as "0" if the thread isn't blocked or we lack the permissions to see it
+    let wchan = fs::read_to_string(format!("{}/wchan", dir))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty() && s != "0");
+
+    Ok(SchedInfo {
+        state: stat.state,
+        wchan,
+        cpu: stat.cpu,
+        utime: ticks(stat.utime),
+        stime: ticks(stat.stime),
+        voluntary_context_switches: status_field(&status, "voluntary_ctxt_switches"),
+        nonvoluntary_context_switches: status_field(&status, "nonvoluntary_ctxt_switches"),
+    })
+}
+
+/// Reads the scheduler state of the main thread of a process.
+pub fn process_state(pid: u32) -> io::Result<ThreadState> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid))?;
+    parse_stat(&stat)
+        .map(|s| s.state)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed stat file"))
+}
+
+struct Stat {
+    state: ThreadState,
+    utime: u64,
+    stime: u64,
+    cpu: Option<u32>,
+}
+
+// 1234 (name) S 1 1234 1234 0 -1 4194560 ...
+fn parse_stat(stat: &str) -> Option<Stat> {
+    // the command name can itself contain spaces and parentheses, so skip past the last closing parenthesis
+    let stat = &stat[stat.rfind(')')? + 1..];
+    let fields = stat.split_whitespace().collect::<Vec<_>>();
+
+    // indices are offset by the 3 fields up to and including the state
+    Some(Stat {
+        state: ThreadState::from_char(fields.first()?.chars().next()?),
+        utime: fields.get(11)?.parse().ok()?,
+        stime: fields.get(12)?.parse().ok()?,
+        cpu: fields.get(36).and_then(|s| s.parse().ok()),
+    })
+}
+
+fn status_field(status: &str, name: &str) -> Option<u64> {
+    status
+        .lines()
+        .filter_map(|l| {
+            let mut it = l.splitn(2, ':');
+            Some((it.next()?, it.next()?))
+        })
+        .find(|(key, _)| *key == name)
+        .and_then(|(_, value)| value.trim().parse().ok())
+}
+
+fn ticks(ticks: u64) -> Duration {
+    let hz = match unsafe { sysconf(_SC_CLK_TCK) } {
+        hz if hz > 0 => hz as u64,
+        _ => 100,
+    };
+
+    Duration::from_secs(ticks / hz) + Duration::from_nanos((ticks % hz) * 1_000_000_000 / hz)
+}
+
+/// Reads the kernel stack of a thread.
+///
+/// Like the scheduler state, this must be read before the thread is stopped by ptrace or it will just show the ptrace
+/// stop itself.
+pub fn kernel_stack(pid: u32, tid: u32) -> result::Result<Vec<KernelFrame>, KernelStackError> {
+    let dir = format!("/proc/{}/task/{}", pid, tid);
+
+    let stack = match fs::read_to_string(format!("{}/stack", dir)) {
+        Ok(stack) => stack,
+        Err(e) => {
+            return Err(match e.raw_os_error() {
+                Some(EACCES) | Some(EPERM) => KernelStackError::PermissionDenied,
+                // the file only exists if the kernel was built with CONFIG_STACKTRACE
+                _ if e.kind() == io::ErrorKind::NotFound && Path::new(&dir).exists() => {
+                    KernelStackError::NotSupported
+                }
+                _ => KernelStackError::Io(e.kind()),
+            });
+        }
+    };
+
+    Ok(stack.lines().filter_map(parse_kernel_frame).collect())
+}
+
+// [<ffffffff8112a7b5>] futex_wait_queue_me+0xc5/0x120 [module]
+fn parse_kernel_frame(line: &str) -> Option<KernelFrame> {
+    let line = line.strip_prefix("[<")?;
+    let end = line.find(">]")?;
+    // the kernel hides addresses from unprivileged readers by printing 0
+    let address = u64::from_str_radix(&line[..end], 16)
+        .ok()
+        .filter(|a| *a != 0);
+
+    let mut it = line[end + 2..].split_whitespace();
+    let symbol = it.next()?;
+    let module = it
+        .next()
+        .and_then(|m| m.strip_prefix('['))
+        .and_then(|m| m.strip_suffix(']'))
+        .map(|m| m.to_string());
+
+    let (symbol, offset, size) = match symbol.rsplit_once('+') {
+        Some((symbol, range)) => {
+            let (offset, size) = range.split_once('/')?;
+            (symbol, hex(offset)?, hex(size)?)
+        }
+        None => (symbol, 0, 0),
+    };
+
+    Some(KernelFrame {
+        address,
+        symbol: symbol.to_string(),
+        offset,
+        size,
+        module,
+    })
+}
+
+fn hex(s: &str) -> Option<u64> {
+    u64::from_str_radix(s.strip_prefix("0x")?, 16).ok()
+}
+
+/// Reads the system call a thread is blocked in, if any.
+///
+/// This must be called before the thread is attached to, since the kernel doesn't report the syscalls of stopped
+/// threads. It's used as a fallback when the syscall can't be read from the registers of the stopped thread.
+pub fn syscall(pid: u32, tid: u32) -> io::Result<Option<Syscall>> {
+    let syscall = fs::read_to_string(format!("/proc/{}/task/{}/syscall", pid, tid))?;
+    Ok(parse_syscall(&syscall))
+}
+
+// 202 0x7f1c2e7fc9d0 0x80 0x0 0x0 0x0 0x0 0x7ffd4c3b8f20 0x7f1c2f0a8d2d
+//
+// The number is -1 if the thread is blocked outside of a syscall, and the file just contains "running" if it isn't
+// blocked at all.
+fn parse_syscall(s: &str) -> Option<Syscall> {
+    let mut it = s.split_whitespace();
+    let number = it.next()?.parse::<u64>().ok()?;
+
+    let mut args = [0; 6];
+    for arg in &mut args {
+        let s = it.next()?;
+        *arg = u64::from_str_radix(s.strip_prefix("0x").unwrap_or(s), 16).ok()?;
+    }
+
+    Some(Syscall { number, args })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_stat_name_with_parens() {
+        let stat = parse_stat(
+            "1234 (my (weird) name) S 1 1234 1234 0 -1 4194560 100 0 0 0 25 13 0 0 20 0 1 0 12345 1000000 200 \
+             18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 3 0 0 0 0 0\n",
+        )
+        .unwrap();
+        assert_eq!(stat.state, ThreadState::Sleeping);
+        assert_eq!(stat.utime, 25);
+        assert_eq!(stat.stime, 13);
+        assert_eq!(stat.cpu, Some(3));
+    }
+
+    #[test]
+    fn parse_stat_without_cpu() {
+        let stat = parse_stat("42 (worker) D 1 42 42 0 -1 4194560 100 0 0 0 7 9 0 0 20 0 1 0 12345\n").unwrap();
+        assert_eq!(stat.state, ThreadState::DiskSleep);
+        assert_eq!(stat.utime, 7);
+        assert_eq!(stat.stime, 9);
+        assert_eq!(stat.cpu, None);
+    }
+
+    #[test]
+    fn parse_stat_truncated() {
+        assert!(parse_stat("42 (worker) R 1 42").is_none());
+        assert!(parse_stat("42 worker R 1 42").is_none());
+    }
+
+    #[test]
+    fn parse_kernel_frame_module() {
+        let frame = parse_kernel_frame("[<ffffffffc05a2b31>] nfs_wait_bit_killable+0x21/0x90 [nfs]").unwrap();
+        assert_eq!(frame.address, Some(0xffffffffc05a2b31));
+        assert_eq!(frame.symbol, "nfs_wait_bit_killable");
+        assert_eq!(frame.offset, 0x21);
+        assert_eq!(frame.size, 0x90);
+        assert_eq!(frame.module.as_deref(), Some("nfs"));
+    }
+
+    #[test]
+    fn parse_kernel_frame_hidden_address() {
+        let frame = parse_kernel_frame("[<0>] futex_wait_queue_me+0xc5/0x120").unwrap();
+        assert_eq!(frame.address, None);
+        assert_eq!(frame.symbol, "futex_wait_queue_me");
+        assert_eq!(frame.offset, 0xc5);
+        assert_eq!(frame.size, 0x120);
+        assert_eq!(frame.module, None);
+    }
+
+    #[test]
+    fn parse_kernel_frame_without_offset() {
+        let frame = parse_kernel_frame("[<ffffffff81a00098>] entry_SYSCALL_64_after_hwframe").unwrap();
+        assert_eq!(frame.address, Some(0xffffffff81a00098));
+        assert_eq!(frame.symbol, "entry_SYSCALL_64_after_hwframe");
+        assert_eq!(frame.offset, 0);
+        assert_eq!(frame.size, 0);
+    }
+
+    #[test]
+    fn parse_kernel_frame_malformed() {
+        assert!(parse_kernel_frame("futex_wait_queue_me+0xc5/0x120").is_none());
+        assert!(parse_kernel_frame("[<0>] futex_wait_queue_me+0xc5").is_none());
+    }
+
+    #[test]
+    fn parse_syscall_blocked() {
+        let syscall = parse_syscall(
+            "232 0x4 0x7ffd4c3b8f20 0x1 0xffffffffffffffff 0x0 0x0 0x7ffd4c3b8f08 0x7f1c2f0a8d2d\n",
+        )
+        .unwrap();
+        assert_eq!(syscall.number, 232);
+        assert_eq!(
+            syscall.args,
+            [0x4, 0x7ffd4c3b8f20, 0x1, 0xffffffffffffffff, 0, 0]
+        );
+    }
+
+    #[test]
+    fn parse_syscall_not_in_syscall() {
+        assert!(parse_syscall("-1 0x7ffd4c3b8f20 0x7f1c2f0a8d2d\n").is_none());
+    }
+
+    #[test]
+    fn parse_syscall_running() {
+        assert!(parse_syscall("running\n").is_none());
+    }
+}
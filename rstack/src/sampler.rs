@@ -0,0 +1,203 @@
+use log::debug;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{imp, proc, Error, ErrorInner, Frame, Result, ThreadState, TraceOptions};
+
+/// A sampling profiler which repeatedly traces the threads of a process.
+///
+/// The unwinding backend's view of the process is reused across samples, so each sample is significantly cheaper than
+/// a separate call to `TraceOptions::trace`.
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    interval: Duration,
+    duration: Duration,
+    options: TraceOptions,
+}
+
+impl Default for Sampler {
+    fn default() -> Sampler {
+        Sampler {
+            interval: Duration::from_millis(10),
+            duration: Duration::from_secs(1),
+            options: TraceOptions::new(),
+        }
+    }
+}
+
+impl Sampler {
+    /// Returns a new `Sampler` with default settings.
+    pub fn new() -> Sampler {
+        Sampler::default()
+    }
+
+    /// Sets the interval between samples.
+    ///
+    /// Defaults to 10 milliseconds.
+    pub fn interval(&mut self, interval: Duration) -> &mut Sampler {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the total length of time to sample for.
+    ///
+    /// Defaults to 1 second.
+    pub fn duration(&mut self, duration: Duration) -> &mut Sampler {
+        self.duration = duration;
+        self
+    }
+
+    /// Sets the options used to trace the process for each sample.
+    ///
+    /// Stacks are aggregated by thread name, so `TraceOptions::thread_names` should be enabled to distinguish
+    /// between threads.
+    ///
+    /// Defaults to `TraceOptions::new()`.
+    pub fn trace_options(&mut self, options: TraceOptions) -> &mut Sampler {
+        self.options = options;
+        self
+    }
+
+    /// Samples the stacks of the threads of the specified process.
+    ///
+    /// Sampling stops early if the process exits, in which case the returned profile contains the samples taken up to
+    /// that point. Any other error encountered while sampling is returned.
+    pub fn run(&self, pid: u32) -> Result<Profile> {
+        let mut state = imp::State::new(pid).map_err(|e| Error(ErrorInner::Unwind(e)))?;
+
+        let mut stacks = HashMap::new();
+        let mut samples = 0;
+        let start = Instant::now();
+        let mut next = start;
+
+        loop {
+            if samples > 0 {
+                if let Err(e) = state.refresh() {
+                    debug!("error refreshing state of process {}: {}", pid, e);
+                }
+            }
+
+            let process = match self.options.trace_with_state(pid, &mut state) {
+                Ok(process) => process,
+                Err(e) if samples > 0 && exited(pid, &e) => {
+                    debug!("process {} exited while sampling: {}", pid, e);
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+            samples += 1;
+
+            for thread in process.threads {
+                let ips = thread.frames.iter().map(|f| f.ip).collect::<Vec<_>>();
+                stacks
+                    .entry((thread.name.clone(), ips))
+                    .or_insert_with(|| SampledStack {
+                        thread_name: thread.name,
+                        frames: thread.frames,
+                        count: 0,
+                    })
+                    .count += 1;
+            }
+
+            next += self.interval;
+            if next.duration_since(start) >= self.duration {
+                break;
+            }
+
+            let now = Instant::now();
+            if next > now {
+                thread::sleep(next - now);
+            } else {
+                // we've fallen behind, so don't try to catch up with a burst of samples
+                next = now;
+            }
+        }
+
+        let mut stacks = stacks.into_values().collect::<Vec<_>>();
+        stacks.sort_by_key(|s| Reverse(s.count));
+
+        Ok(Profile {
+            id: pid,
+            samples,
+            stacks,
+        })
+    }
+}
+
+// Tracing a process which has exited fails with ESRCH from ptrace, but depending on where it was interrupted the error
+// may instead come from procfs or the unwinder, so fall back to checking if the process is still around. An exited
+// process which hasn't been reaped yet still has an entry in /proc, but it's a zombie.
+fn exited(pid: u32, error: &Error) -> bool {
+    if let Some(e) = error.as_io_error() {
+        if e.raw_os_error() == Some(libc::ESRCH) {
+            return true;
+        }
+    }
+
+    match proc::process_state(pid) {
+        Ok(ThreadState::Zombie) | Ok(ThreadState::Dead) => true,
+        Ok(_) => false,
+        Err(_) => true,
+    }
+}
+
+/// The aggregated stacks collected by a `Sampler`.
+#[derive(Debug, Clone)]
+pub struct Profile {
+    id: u32,
+    samples: u64,
+    stacks: Vec<SampledStack>,
+}
+
+impl Profile {
+    /// Returns the ID of the sampled process.
+    #[inline]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns the number of times the process was sampled.
+    #[inline]
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Returns the unique stacks observed while sampling, most frequent first.
+    #[inline]
+    pub fn stacks(&self) -> &[SampledStack] {
+        &self.stacks
+    }
+}
+
+/// A unique stack observed by a `Sampler`, along with the number of times it was seen.
+#[derive(Debug, Clone)]
+pub struct SampledStack {
+    thread_name: Option<String>,
+    frames: Vec<Frame>,
+    count: u64,
+}
+
+impl SampledStack {
+    /// Returns the name of the thread the stack was observed in, if known.
+    #[inline]
+    pub fn thread_name(&self) -> Option<&str> {
+        self.thread_name.as_deref()
+    }
+
+    /// Returns the frames of the stack.
+    ///
+    /// Stacks are identified by the instruction pointers of their frames. Other information, such as symbols, is taken
+    /// from the first sample the stack was observed in.
+    #[inline]
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Returns the number of times the stack was observed.
+    #[inline]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
@@ -0,0 +1,246 @@
+//! The serialized representation of traces.
+//!
+//! Traces are serialized with an explicit schema version so that consumers can detect traces they don't know how to
+//! interpret. The version must be incremented whenever a change is made to the serialized form of the trace model
+//! which would prevent older versions of this crate from deserializing it.
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+use crate::{Process, Thread};
+
+// version 2 added the unwind error of each thread
+const VERSION: u32 = 2;
+// the oldest version which can still be deserialized, since the fields added since then are optional
+const MIN_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+pub struct ProcessRepr {
+    version: u32,
+    id: u32,
+    threads: Vec<Thread>,
+    snapshot_converged: Option<bool>,
+}
+
+impl From<Process> for ProcessRepr {
+    fn from(process: Process) -> ProcessRepr {
+        ProcessRepr {
+            version: VERSION,
+            id: process.id,
+            threads: process.threads,
+            snapshot_converged: process.snapshot_converged,
+        }
+    }
+}
+
+impl TryFrom<ProcessRepr> for Process {
+    type Error = String;
+
+    fn try_from(repr: ProcessRepr) -> Result<Process, String> {
+        if repr.version < MIN_VERSION || repr.version > VERSION {
+            return Err(format!(
+                "unsupported trace schema version {} (expected {} through {})",
+                repr.version, MIN_VERSION, VERSION
+            ));
+        }
+
+        Ok(Process {
+            id: repr.id,
+            threads: repr.threads,
+            snapshot_converged: repr.snapshot_converged,
+        })
+    }
+}
+
+/// Serializes `io::ErrorKind` by the name of its variant.
+pub mod error_kind {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::io;
+
+    const KINDS: &[io::ErrorKind] = &[
+        io::ErrorKind::NotFound,
+        io::ErrorKind::PermissionDenied,
+        io::ErrorKind::ConnectionRefused,
+        io::ErrorKind::ConnectionReset,
+        io::ErrorKind::ConnectionAborted,
+        io::ErrorKind::NotConnected,
+        io::ErrorKind::AddrInUse,
+        io::ErrorKind::AddrNotAvailable,
+        io::ErrorKind::BrokenPipe,
+        io::ErrorKind::AlreadyExists,
+        io::ErrorKind::WouldBlock,
+        io::ErrorKind::InvalidInput,
+        io::ErrorKind::InvalidData,
+        io::ErrorKind::TimedOut,
+        io::ErrorKind::WriteZero,
+        io::ErrorKind::Interrupted,
+        io::ErrorKind::Unsupported,
+        io::ErrorKind::UnexpectedEof,
+        io::ErrorKind::OutOfMemory,
+        io::ErrorKind::Other,
+    ];
+
+    pub fn serialize<S>(kind: &io::ErrorKind, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(&format_args!("{:?}", kind))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<io::ErrorKind, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        // kinds added in newer versions of Rust fall back to Other
+        let kind = KINDS
+            .iter()
+            .find(|k| format!("{:?}", k) == name)
+            .copied()
+            .unwrap_or(io::ErrorKind::Other);
+        Ok(kind)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::VERSION;
+    use crate::test_util;
+    use crate::{
+        Frame, InlinedFrame, KernelStackError, Location, Process, Register, Registers, SchedInfo, Symbol, Syscall,
+        Thread, ThreadState, Truncation, UnwindError,
+    };
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    fn full_process() -> Process {
+        Process {
+            id: 1234,
+            threads: vec![Thread {
+                id: 1235,
+                name: Some("worker".to_string()),
+                sched_info: Some(SchedInfo {
+                    state: ThreadState::Other('W'),
+                    wchan: Some("futex_wait_queue_me".to_string()),
+                    cpu: Some(3),
+                    utime: Duration::from_millis(250),
+                    stime: Duration::from_millis(130),
+                    voluntary_context_switches: Some(17),
+                    nonvoluntary_context_switches: None,
+                }),
+                kernel_stack: Some(Err(KernelStackError::Io(io::ErrorKind::NotFound))),
+                syscall: Some(Syscall {
+                    number: 202,
+                    args: [0x7f1c2e7fc9d0, 0x80, 0, 0, 0, 0],
+                }),
+                truncation: Some(Truncation::MaxFrames),
+                error: None,
+                frames: vec![Frame {
+                    ip: 0x5555_5555_1234,
+                    is_signal: false,
+                    symbol: Some(Symbol {
+                        name: "_ZN3foo3barE".to_string(),
+                        demangled_name: Some("foo::bar".to_string()),
+                        offset: 0x34,
+                        address: 0x5555_5555_1200,
+                        size: 0x80,
+                    }),
+                    location: Some(Location {
+                        file: PathBuf::from("src/foo.rs"),
+                        line: Some(12),
+                        column: None,
+                    }),
+                    inlined_frames: vec![InlinedFrame {
+                        name: Some("baz".to_string()),
+                        demangled_name: None,
+                        call_location: None,
+                    }],
+                    registers: Some(Registers {
+                        sp: Some(0x7ffd_0000_1000),
+                        cfa: None,
+                        callee_saved: vec![Register {
+                            name: "rbx".into(),
+                            value: 42,
+                        }],
+                    }),
+                }],
+            }],
+            snapshot_converged: Some(true),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let json = serde_json::to_value(full_process()).unwrap();
+        assert_eq!(json["version"], VERSION);
+
+        let process = serde_json::from_value::<Process>(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&process).unwrap(), json);
+
+        assert_eq!(process.id(), 1234);
+        assert_eq!(process.snapshot_converged(), Some(true));
+        let thread = &process.threads()[0];
+        assert_eq!(thread.name(), Some("worker"));
+        let sched_info = thread.sched_info().unwrap();
+        assert_eq!(sched_info.state(), ThreadState::Other('W'));
+        assert_eq!(sched_info.utime(), Duration::from_millis(250));
+        match thread.kernel_stack_error() {
+            Some(KernelStackError::Io(io::ErrorKind::NotFound)) => {}
+            error => panic!("unexpected kernel stack error {:?}", error),
+        }
+        assert_eq!(thread.syscall().unwrap().number(), 202);
+        assert_eq!(thread.truncation(), Some(Truncation::MaxFrames));
+        let frame = &thread.frames()[0];
+        assert_eq!(frame.ip(), 0x5555_5555_1234);
+        assert_eq!(frame.symbol().unwrap().demangled_name(), Some("foo::bar"));
+        assert_eq!(frame.location().unwrap().file(), Path::new("src/foo.rs"));
+        assert_eq!(frame.inlined_frames()[0].name(), Some("baz"));
+        assert_eq!(frame.registers().unwrap().callee_saved()[0].name(), "rbx");
+    }
+
+    #[test]
+    fn older_version() {
+        let mut json = serde_json::to_value(full_process()).unwrap();
+        json["version"] = 1.into();
+        json["threads"][0].as_object_mut().unwrap().remove("error");
+
+        let process = serde_json::from_value::<Process>(json).unwrap();
+        assert!(process.threads()[0].error().is_none());
+    }
+
+    #[test]
+    fn unknown_versions() {
+        for &version in &[0, VERSION + 1] {
+            let mut json = serde_json::to_value(full_process()).unwrap();
+            json["version"] = version.into();
+
+            let error = serde_json::from_value::<Process>(json).unwrap_err();
+            assert!(
+                error
+                    .to_string()
+                    .contains(&format!("unsupported trace schema version {}", version)),
+                "{}",
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn unwind_error_round_trip() {
+        let mut thread = test_util::thread(1235, vec![]);
+        thread.error = Some(UnwindError {
+            message: "bad frame".to_string(),
+            frame: 3,
+            error: None,
+        });
+        let process = test_util::process(vec![thread]);
+
+        let json = serde_json::to_string(&process).unwrap();
+        let process = serde_json::from_str::<Process>(&json).unwrap();
+
+        let error = process.threads()[0].error().unwrap();
+        assert_eq!(error.message(), "bad frame");
+        assert_eq!(error.frame(), 3);
+        assert_eq!(error.to_string(), "error unwinding frame 3: bad frame");
+    }
+}
@@ -0,0 +1,229 @@
+//! Inspection of the system calls which traced threads are blocked in.
+use cfg_if::cfg_if;
+use std::io;
+
+use crate::Syscall;
+
+// futex operations which block the calling thread, from linux/futex.h
+const FUTEX_WAIT: u64 = 0;
+const FUTEX_LOCK_PI: u64 = 6;
+const FUTEX_WAIT_BITSET: u64 = 9;
+const FUTEX_WAIT_REQUEUE_PI: u64 = 11;
+const FUTEX_LOCK_PI2: u64 = 13;
+const FUTEX_PRIVATE_FLAG: u64 = 128;
+const FUTEX_CLOCK_REALTIME: u64 = 256;
+
+// syscalls added since Linux 5.1 have the same number on all architectures
+const SYS_FUTEX_WAIT: u64 = 455;
+
+/// Returns the address of the futex the syscall is waiting on, if it is a blocking futex operation.
+///
+/// `futex_waitv` isn't handled since it waits on an array of futexes rather than a single one, and the array lives in
+/// the memory of the traced process.
+pub fn futex_address(syscall: &Syscall) -> Option<u64> {
+    if syscall.number == SYS_FUTEX_WAIT {
+        return Some(syscall.args[0]);
+    }
+
+    if syscall.number != libc::SYS_futex as u64 {
+        return None;
+    }
+
+    match syscall.args[1] & !(FUTEX_PRIVATE_FLAG | FUTEX_CLOCK_REALTIME) {
+        FUTEX_WAIT | FUTEX_LOCK_PI | FUTEX_WAIT_BITSET | FUTEX_WAIT_REQUEUE_PI | FUTEX_LOCK_PI2 => {
+            Some(syscall.args[0])
+        }
+        _ => None,
+    }
+}
+
+cfg_if! {
+    if #[cfg(target_arch = "x86_64")] {
+        pub use self::x86_64::name;
+
+        mod x86_64;
+
+        use libc::{c_long, c_void, pid_t, ptrace, user_regs_struct, PTRACE_GETREGS};
+        use std::mem;
+        use std::ptr;
+
+        /// Returns the syscall the thread was blocked in when it was stopped, if any.
+        ///
+        /// The thread must be in a ptrace stop.
+        pub fn read(tid: u32) -> io::Result<Option<Syscall>> {
+            let regs = unsafe {
+                let mut regs = mem::zeroed::<user_regs_struct>();
+                let ret = ptrace(
+                    PTRACE_GETREGS,
+                    tid as pid_t,
+                    ptr::null_mut::<c_void>(),
+                    &mut regs as *mut user_regs_struct as *mut c_void,
+                );
+                if ret != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                regs
+            };
+
+            // orig_rax is -1 outside of a syscall. rax isn't checked since an interrupted syscall can hold a restart
+            // errno, -EINTR for syscalls like epoll_wait which are never restarted, or -ETIMEDOUT for some futex ops.
+            if (regs.orig_rax as c_long) < 0 {
+                return Ok(None);
+            }
+
+            Ok(Some(Syscall {
+                number: regs.orig_rax,
+                args: [regs.rdi, regs.rsi, regs.rdx, regs.r10, regs.r8, regs.r9],
+            }))
+        }
+    } else if #[cfg(target_arch = "aarch64")] {
+        pub use self::aarch64::name;
+
+        mod aarch64;
+
+        use libc::{c_int, c_void, iovec, pid_t, ptrace, user_regs_struct, PTRACE_GETREGSET};
+        use std::mem;
+
+        // register sets, from linux/elf.h
+        const NT_PRSTATUS: usize = 1;
+        const NT_ARM_SYSTEM_CALL: usize = 0x404;
+
+        /// Returns the syscall the thread was blocked in when it was stopped, if any.
+        ///
+        /// The thread must be in a ptrace stop.
+        pub fn read(tid: u32) -> io::Result<Option<Syscall>> {
+            // the kernel rewinds an interrupted syscall before the ptrace stop, so the syscall number is still set
+            let mut number = -1 as c_int;
+            get_regset(tid, NT_ARM_SYSTEM_CALL, &mut number)?;
+            if number < 0 {
+                return Ok(None);
+            }
+
+            let mut regs = unsafe { mem::zeroed::<user_regs_struct>() };
+            get_regset(tid, NT_PRSTATUS, &mut regs)?;
+
+            Ok(Some(Syscall {
+                number: number as u64,
+                args: [
+                    regs.regs[0],
+                    regs.regs[1],
+                    regs.regs[2],
+                    regs.regs[3],
+                    regs.regs[4],
+                    regs.regs[5],
+                ],
+            }))
+        }
+
+        fn get_regset<T>(tid: u32, regset: usize, value: &mut T) -> io::Result<()> {
+            let mut iov = iovec {
+                iov_base: value as *mut T as *mut c_void,
+                iov_len: mem::size_of::<T>(),
+            };
+            let ret = unsafe {
+                ptrace(
+                    PTRACE_GETREGSET,
+                    tid as pid_t,
+                    regset as *mut c_void,
+                    &mut iov as *mut iovec as *mut c_void,
+                )
+            };
+            if ret != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+    } else {
+        /// Returns the name of the syscall with the specified number.
+        pub fn name(_: u64) -> Option<&'static str> {
+            None
+        }
+
+        /// Returns the syscall the thread was blocked in when it was stopped, if any.
+        ///
+        /// Only x86_64 and aarch64 are currently supported.
+        pub fn read(_: u32) -> io::Result<Option<Syscall>> {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                "reading syscall registers is not supported on this architecture",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn futex(op: u64) -> Syscall {
+        Syscall {
+            number: libc::SYS_futex as u64,
+            args: [0x7f00_1234_5678, op, 2, 0, 0, 0],
+        }
+    }
+
+    #[test]
+    fn futex_address_wait() {
+        assert_eq!(futex_address(&futex(FUTEX_WAIT)), Some(0x7f00_1234_5678));
+        assert_eq!(
+            futex_address(&futex(FUTEX_WAIT | FUTEX_PRIVATE_FLAG)),
+            Some(0x7f00_1234_5678)
+        );
+        assert_eq!(
+            futex_address(&futex(
+                FUTEX_WAIT_BITSET | FUTEX_PRIVATE_FLAG | FUTEX_CLOCK_REALTIME
+            )),
+            Some(0x7f00_1234_5678),
+        );
+    }
+
+    #[test]
+    fn futex_address_wake() {
+        // FUTEX_WAKE doesn't block
+        assert_eq!(futex_address(&futex(1 | FUTEX_PRIVATE_FLAG)), None);
+    }
+
+    #[test]
+    fn futex_address_futex_wait() {
+        let syscall = Syscall {
+            number: SYS_FUTEX_WAIT,
+            args: [0x7f00_1234_5678, 2, u32::MAX as u64, 0x82, 0, 0],
+        };
+        assert_eq!(futex_address(&syscall), Some(0x7f00_1234_5678));
+    }
+
+    #[test]
+    fn futex_address_futex_waitv() {
+        let syscall = Syscall {
+            number: 449,
+            args: [0x7f00_1234_5678, 2, 0, 0, 1, 0],
+        };
+        assert_eq!(futex_address(&syscall), None);
+    }
+
+    #[test]
+    fn futex_address_other_syscall() {
+        let syscall = Syscall {
+            number: libc::SYS_read as u64,
+            args: [3, 0x7f00_1234_5678, 4096, 0, 0, 0],
+        };
+        assert_eq!(futex_address(&syscall), None);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(name(libc::SYS_read as u64), Some("read"));
+        assert_eq!(name(libc::SYS_futex as u64), Some("futex"));
+        #[cfg(target_arch = "x86_64")]
+        assert_eq!(name(333), Some("io_pgetevents"));
+        #[cfg(target_arch = "aarch64")]
+        assert_eq!(name(292), Some("io_pgetevents"));
+        assert_eq!(name(449), Some("futex_waitv"));
+        assert_eq!(name(451), Some("cachestat"));
+        assert_eq!(name(452), Some("fchmodat2"));
+        assert_eq!(name(455), Some("futex_wait"));
+        assert_eq!(name(461), Some("lsm_list_modules"));
+        assert_eq!(name(462), Some("mseal"));
+        assert_eq!(name(u64::MAX), None);
+    }
+}
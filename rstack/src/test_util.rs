@@ -0,0 +1,59 @@
+use crate::{Frame, InlinedFrame, Process, Symbol, Thread};
+
+/// Creates a frame at the specified address, in a 256 byte symbol if a name is provided.
+pub fn frame(ip: u64, name: Option<&str>) -> Frame {
+    Frame {
+        ip,
+        is_signal: false,
+        symbol: name.map(|name| Symbol {
+            name: name.to_string(),
+            demangled_name: None,
+            offset: ip & 0xff,
+            address: ip & !0xff,
+            size: 0x100,
+        }),
+        location: None,
+        inlined_frames: vec![],
+        registers: None,
+    }
+}
+
+/// Creates unsymbolized frames at the specified addresses.
+pub fn frames(ips: &[u64]) -> Vec<Frame> {
+    ips.iter().map(|&ip| frame(ip, None)).collect()
+}
+
+/// Creates inlined frames with the specified names, innermost first.
+pub fn inlined_frames(names: &[&str]) -> Vec<InlinedFrame> {
+    names
+        .iter()
+        .map(|name| InlinedFrame {
+            name: Some(name.to_string()),
+            demangled_name: None,
+            call_location: None,
+        })
+        .collect()
+}
+
+/// Creates a thread with the specified frames and nothing else recorded.
+pub fn thread(id: u32, frames: Vec<Frame>) -> Thread {
+    Thread {
+        id,
+        name: None,
+        sched_info: None,
+        kernel_stack: None,
+        syscall: None,
+        truncation: None,
+        error: None,
+        frames,
+    }
+}
+
+/// Creates a process with the specified threads.
+pub fn process(threads: Vec<Thread>) -> Process {
+    Process {
+        id: 1,
+        threads,
+        snapshot_converged: None,
+    }
+}
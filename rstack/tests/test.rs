@@ -0,0 +1,137 @@
+use std::env;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+const CHILD_ENV: &str = "RSTACK_TEST_CHILD";
+const READY: &str = "rstack-test-child-ready";
+
+struct ChildGuard(Child);
+
+impl Drop for ChildGuard {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+impl ChildGuard {
+    fn id(&self) -> u32 {
+        self.0.id()
+    }
+}
+
+// Runs one of the ignored child tests below in a new copy of this test binary, returning once it's about to block.
+fn spawn_child(name: &str) -> ChildGuard {
+    let mut child = Command::new(env::current_exe().unwrap())
+        .args([
+            name,
+            "--exact",
+            "--ignored",
+            "--nocapture",
+            "--test-threads=1",
+        ])
+        .env(CHILD_ENV, "1")
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let child = ChildGuard(child);
+
+    for line in BufReader::new(stdout).lines() {
+        if line.unwrap().contains(READY) {
+            return child;
+        }
+    }
+    panic!("child exited before becoming ready");
+}
+
+// The child may not have blocked yet when it reports that it's ready, so retry for a while.
+fn trace_until<F>(pid: u32, mut f: F)
+where
+    F: FnMut(&rstack::Process) -> bool,
+{
+    for _ in 0..50 {
+        let process = rstack::TraceOptions::new()
+            .blocking_info(true)
+            .symbols(false)
+            .trace(pid)
+            .unwrap();
+        if f(&process) {
+            return;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    panic!("child never reached the expected state");
+}
+
+#[test]
+#[ignore]
+fn child_epoll_wait() {
+    if env::var_os(CHILD_ENV).is_none() {
+        return;
+    }
+
+    unsafe {
+        let fd = libc::epoll_create1(0);
+        assert!(fd >= 0);
+        println!("{}", READY);
+
+        let mut event = libc::epoll_event { events: 0, u64: 0 };
+        loop {
+            libc::epoll_wait(fd, &mut event, 1, -1);
+        }
+    }
+}
+
+#[test]
+fn epoll_wait_syscall() {
+    let child = spawn_child("child_epoll_wait");
+
+    trace_until(child.id(), |process| {
+        process.threads().iter().any(|thread| {
+            match thread.syscall().and_then(|s| s.name()) {
+                // glibc implements epoll_wait with epoll_pwait on some architectures
+                Some(name) => name == "epoll_wait" || name == "epoll_pwait",
+                None => false,
+            }
+        })
+    });
+}
+
+#[test]
+#[ignore]
+fn child_mutex_waiters() {
+    use std::sync::{Arc, Mutex};
+
+    if env::var_os(CHILD_ENV).is_none() {
+        return;
+    }
+
+    let mutex = Arc::new(Mutex::new(()));
+    let _guard = mutex.lock().unwrap();
+    for _ in 0..2 {
+        let mutex = mutex.clone();
+        thread::spawn(move || {
+            let _guard = mutex.lock().unwrap();
+        });
+    }
+    println!("{}", READY);
+
+    loop {
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+#[test]
+fn mutex_waiters() {
+    let child = spawn_child("child_mutex_waiters");
+
+    trace_until(child.id(), |process| {
+        process
+            .futex_waiters()
+            .iter()
+            .any(|waiters| waiters.threads().len() == 2)
+    });
+}
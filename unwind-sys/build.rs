@@ -27,6 +27,9 @@ fn main() {
     if major < 1 || (major == 1 && minor < 4) {
         println!("cargo:rustc-cfg=pre14");
     }
+    if major < 1 || (major == 1 && minor < 5) {
+        println!("cargo:rustc-cfg=pre15");
+    }
     if major < 1 || (major == 1 && minor < 6) {
         println!("cargo:rustc-cfg=pre16");
     }
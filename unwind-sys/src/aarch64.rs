@@ -15,6 +15,13 @@ cfg_if! {
 pub type unw_word_t = u64;
 pub type unw_sword_t = i64;
 
+// a 128 bit long double, which has no Rust equivalent
+#[repr(C, align(16))]
+#[derive(Copy, Clone)]
+pub struct unw_tdep_fpreg_t {
+    pub bytes: [u8; 16],
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct unw_tdep_proc_info_t {}
@@ -237,6 +244,13 @@ extern "C" {
     #[link_name = "_Uaarch64_set_reg"]
     pub fn unw_set_reg(cur: *mut unw_cursor_t, reg: unw_regnum_t, val: unw_word_t) -> c_int;
 
+    #[link_name = "_Uaarch64_get_fpreg"]
+    pub fn unw_get_fpreg(
+        cur: *mut unw_cursor_t,
+        reg: unw_regnum_t,
+        valp: *mut unw_fpreg_t,
+    ) -> c_int;
+
     #[link_name = "_Uaarch64_resume"]
     pub fn unw_resume(cur: *mut unw_cursor_t) -> c_int;
 
@@ -281,6 +295,16 @@ extern "C" {
         offp: *mut unw_word_t,
     ) -> c_int;
 
+    #[link_name = "_Uaarch64_dwarf_search_unwind_table"]
+    pub fn dwarf_search_unwind_table(
+        spc: unw_addr_space_t,
+        ip: unw_word_t,
+        di: *mut unw_dyn_info_t,
+        pi: *mut unw_proc_info_t,
+        need_unwind_info: c_int,
+        arg: *mut c_void,
+    ) -> c_int;
+
     #[link_name = "_Uaarch64_strerror"]
     pub fn unw_strerror(err_code: c_int) -> *const c_char;
 
@@ -56,6 +56,8 @@ pub struct unw_cursor_t {
 
 pub type unw_context_t = unw_tdep_context_t;
 
+pub type unw_fpreg_t = unw_tdep_fpreg_t;
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct unw_proc_info_t {
@@ -83,11 +85,7 @@ pub struct unw_accessors_t {
         ) -> c_int,
     >,
     pub put_unwind_info: Option<
-        unsafe extern "C" fn(
-            asp: unw_addr_space_t,
-            pip: *mut unw_proc_info_t,
-            arg: *mut c_void,
-        ) -> c_void,
+        unsafe extern "C" fn(asp: unw_addr_space_t, pip: *mut unw_proc_info_t, arg: *mut c_void),
     >,
     pub get_dyn_info_list_addr: Option<
         unsafe extern "C" fn(
@@ -114,8 +112,15 @@ pub struct unw_accessors_t {
             arg: *mut c_void,
         ) -> c_int,
     >,
-    // unw_fpreg_t is a long double :(
-    access_fpreg: Option<unsafe extern "C" fn()>,
+    pub access_fpreg: Option<
+        unsafe extern "C" fn(
+            asp: unw_addr_space_t,
+            regnum: unw_regnum_t,
+            fpvalp: *mut unw_fpreg_t,
+            write: c_int,
+            arg: *mut c_void,
+        ) -> c_int,
+    >,
     pub resume: Option<
         unsafe extern "C" fn(
             asp: unw_addr_space_t,
@@ -135,6 +140,65 @@ pub struct unw_accessors_t {
     >,
 }
 
+pub const UNW_INFO_FORMAT_DYNAMIC: c_int = 0;
+pub const UNW_INFO_FORMAT_TABLE: c_int = 1;
+pub const UNW_INFO_FORMAT_REMOTE_TABLE: c_int = 2;
+pub const UNW_INFO_FORMAT_ARM_EXIDX: c_int = 3;
+pub const UNW_INFO_FORMAT_IP_OFFSET: c_int = 4;
+
+pub enum unw_dyn_region_info_t {}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct unw_dyn_proc_info_t {
+    pub name_ptr: unw_word_t,
+    pub handler: unw_word_t,
+    pub flags: u32,
+    pub pad0: i32,
+    pub regions: *mut unw_dyn_region_info_t,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct unw_dyn_table_info_t {
+    pub name_ptr: unw_word_t,
+    pub segbase: unw_word_t,
+    pub table_len: unw_word_t,
+    pub table_data: *mut unw_word_t,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct unw_dyn_remote_table_info_t {
+    pub name_ptr: unw_word_t,
+    pub segbase: unw_word_t,
+    pub table_len: unw_word_t,
+    pub table_data: unw_word_t,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union unw_dyn_info_t_u {
+    pub pi: unw_dyn_proc_info_t,
+    pub ti: unw_dyn_table_info_t,
+    pub rti: unw_dyn_remote_table_info_t,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct unw_dyn_info_t {
+    pub next: *mut unw_dyn_info_t,
+    pub prev: *mut unw_dyn_info_t,
+    pub start_ip: unw_word_t,
+    pub end_ip: unw_word_t,
+    pub gp: unw_word_t,
+    pub format: i32,
+    pub pad: i32,
+    #[cfg(not(pre15))]
+    pub load_offset: unw_word_t,
+    pub u: unw_dyn_info_t_u,
+}
+
 #[repr(C)]
 pub union unw_save_loc_t_u {
     pub addr: unw_word_t,
@@ -80,6 +80,13 @@ pub struct unw_tdep_save_loc_t {}
 
 pub type unw_tdep_context_t = ucontext_t;
 
+// an 80 bit long double padded to 12 bytes, which has no Rust equivalent
+#[repr(C, align(4))]
+#[derive(Copy, Clone)]
+pub struct unw_tdep_fpreg_t {
+    pub bytes: [u8; 12],
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct unw_tdep_proc_info_t {}
@@ -110,6 +117,13 @@ extern "C" {
     #[link_name = "_Ux86_set_reg"]
     pub fn unw_set_reg(cur: *mut unw_cursor_t, reg: unw_regnum_t, val: unw_word_t) -> c_int;
 
+    #[link_name = "_Ux86_get_fpreg"]
+    pub fn unw_get_fpreg(
+        cur: *mut unw_cursor_t,
+        reg: unw_regnum_t,
+        valp: *mut unw_fpreg_t,
+    ) -> c_int;
+
     #[link_name = "_Ux86_resume"]
     pub fn unw_resume(cur: *mut unw_cursor_t) -> c_int;
 
@@ -154,6 +168,16 @@ extern "C" {
         offp: *mut unw_word_t,
     ) -> c_int;
 
+    #[link_name = "_Ux86_dwarf_search_unwind_table"]
+    pub fn dwarf_search_unwind_table(
+        spc: unw_addr_space_t,
+        ip: unw_word_t,
+        di: *mut unw_dyn_info_t,
+        pi: *mut unw_proc_info_t,
+        need_unwind_info: c_int,
+        arg: *mut c_void,
+    ) -> c_int;
+
     #[link_name = "_Ux86_strerror"]
     pub fn unw_strerror(err_code: c_int) -> *const c_char;
 
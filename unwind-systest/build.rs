@@ -28,6 +28,9 @@ fn main() {
     if major < 1 || (major == 1 && minor < 4) {
         cfg.cfg("pre14", None);
     }
+    if major < 1 || (major == 1 && minor < 5) {
+        cfg.cfg("pre15", None);
+    }
 
     cfg.header("libunwind.h")
         .type_name(|t, _, _| match t {
@@ -47,14 +50,17 @@ fn main() {
         .skip_type(|s| match s {
             // https://github.com/rust-lang/libc/issues/1410
             "unw_tdep_context_t" | "unw_context_t" => true,
+            "unw_fpreg_t" => true,
             _ => false,
         })
         .skip_struct(|s| match s {
-            "unw_save_loc_t_u" => true,
+            "unw_save_loc_t_u" | "unw_dyn_info_t_u" => true,
+            // a long double in C
+            "unw_tdep_fpreg_t" => true,
             _ => false,
         })
         .skip_field_type(|s, f| match (s, f) {
-            ("unw_save_loc_t", "u") => true,
+            ("unw_save_loc_t", "u") | ("unw_dyn_info_t", "u") => true,
             _ => false,
         })
         // i686 ABI disagrees about how to handle ZST-by-value
@@ -0,0 +1,141 @@
+use crate::RegNum;
+use unwind_sys::*;
+
+impl RegNum {
+    /// An aarch64-specific identifier for the X0 register.
+    pub const X0: RegNum = RegNum(UNW_AARCH64_X0);
+    /// An aarch64-specific identifier for the X1 register.
+    pub const X1: RegNum = RegNum(UNW_AARCH64_X1);
+    /// An aarch64-specific identifier for the X2 register.
+    pub const X2: RegNum = RegNum(UNW_AARCH64_X2);
+    /// An aarch64-specific identifier for the X3 register.
+    pub const X3: RegNum = RegNum(UNW_AARCH64_X3);
+    /// An aarch64-specific identifier for the X4 register.
+    pub const X4: RegNum = RegNum(UNW_AARCH64_X4);
+    /// An aarch64-specific identifier for the X5 register.
+    pub const X5: RegNum = RegNum(UNW_AARCH64_X5);
+    /// An aarch64-specific identifier for the X6 register.
+    pub const X6: RegNum = RegNum(UNW_AARCH64_X6);
+    /// An aarch64-specific identifier for the X7 register.
+    pub const X7: RegNum = RegNum(UNW_AARCH64_X7);
+    /// An aarch64-specific identifier for the X8 register.
+    pub const X8: RegNum = RegNum(UNW_AARCH64_X8);
+    /// An aarch64-specific identifier for the X9 register.
+    pub const X9: RegNum = RegNum(UNW_AARCH64_X9);
+    /// An aarch64-specific identifier for the X10 register.
+    pub const X10: RegNum = RegNum(UNW_AARCH64_X10);
+    /// An aarch64-specific identifier for the X11 register.
+    pub const X11: RegNum = RegNum(UNW_AARCH64_X11);
+    /// An aarch64-specific identifier for the X12 register.
+    pub const X12: RegNum = RegNum(UNW_AARCH64_X12);
+    /// An aarch64-specific identifier for the X13 register.
+    pub const X13: RegNum = RegNum(UNW_AARCH64_X13);
+    /// An aarch64-specific identifier for the X14 register.
+    pub const X14: RegNum = RegNum(UNW_AARCH64_X14);
+    /// An aarch64-specific identifier for the X15 register.
+    pub const X15: RegNum = RegNum(UNW_AARCH64_X15);
+    /// An aarch64-specific identifier for the X16 register.
+    pub const X16: RegNum = RegNum(UNW_AARCH64_X16);
+    /// An aarch64-specific identifier for the X17 register.
+    pub const X17: RegNum = RegNum(UNW_AARCH64_X17);
+    /// An aarch64-specific identifier for the X18 register.
+    pub const X18: RegNum = RegNum(UNW_AARCH64_X18);
+    /// An aarch64-specific identifier for the X19 register.
+    pub const X19: RegNum = RegNum(UNW_AARCH64_X19);
+    /// An aarch64-specific identifier for the X20 register.
+    pub const X20: RegNum = RegNum(UNW_AARCH64_X20);
+    /// An aarch64-specific identifier for the X21 register.
+    pub const X21: RegNum = RegNum(UNW_AARCH64_X21);
+    /// An aarch64-specific identifier for the X22 register.
+    pub const X22: RegNum = RegNum(UNW_AARCH64_X22);
+    /// An aarch64-specific identifier for the X23 register.
+    pub const X23: RegNum = RegNum(UNW_AARCH64_X23);
+    /// An aarch64-specific identifier for the X24 register.
+    pub const X24: RegNum = RegNum(UNW_AARCH64_X24);
+    /// An aarch64-specific identifier for the X25 register.
+    pub const X25: RegNum = RegNum(UNW_AARCH64_X25);
+    /// An aarch64-specific identifier for the X26 register.
+    pub const X26: RegNum = RegNum(UNW_AARCH64_X26);
+    /// An aarch64-specific identifier for the X27 register.
+    pub const X27: RegNum = RegNum(UNW_AARCH64_X27);
+    /// An aarch64-specific identifier for the X28 register.
+    pub const X28: RegNum = RegNum(UNW_AARCH64_X28);
+    /// An aarch64-specific identifier for the X29 register.
+    pub const X29: RegNum = RegNum(UNW_AARCH64_X29);
+    /// An aarch64-specific identifier for the X30 register.
+    pub const X30: RegNum = RegNum(UNW_AARCH64_X30);
+    /// An aarch64-specific identifier for the PC register.
+    pub const PC: RegNum = RegNum(UNW_AARCH64_PC);
+    /// An aarch64-specific identifier for the PSTATE register.
+    pub const PSTATE: RegNum = RegNum(UNW_AARCH64_PSTATE);
+    /// An aarch64-specific identifier for the V0 register.
+    pub const V0: RegNum = RegNum(UNW_AARCH64_V0);
+    /// An aarch64-specific identifier for the V1 register.
+    pub const V1: RegNum = RegNum(UNW_AARCH64_V1);
+    /// An aarch64-specific identifier for the V2 register.
+    pub const V2: RegNum = RegNum(UNW_AARCH64_V2);
+    /// An aarch64-specific identifier for the V3 register.
+    pub const V3: RegNum = RegNum(UNW_AARCH64_V3);
+    /// An aarch64-specific identifier for the V4 register.
+    pub const V4: RegNum = RegNum(UNW_AARCH64_V4);
+    /// An aarch64-specific identifier for the V5 register.
+    pub const V5: RegNum = RegNum(UNW_AARCH64_V5);
+    /// An aarch64-specific identifier for the V6 register.
+    pub const V6: RegNum = RegNum(UNW_AARCH64_V6);
+    /// An aarch64-specific identifier for the V7 register.
+    pub const V7: RegNum = RegNum(UNW_AARCH64_V7);
+    /// An aarch64-specific identifier for the V8 register.
+    pub const V8: RegNum = RegNum(UNW_AARCH64_V8);
+    /// An aarch64-specific identifier for the V9 register.
+    pub const V9: RegNum = RegNum(UNW_AARCH64_V9);
+    /// An aarch64-specific identifier for the V10 register.
+    pub const V10: RegNum = RegNum(UNW_AARCH64_V10);
+    /// An aarch64-specific identifier for the V11 register.
+    pub const V11: RegNum = RegNum(UNW_AARCH64_V11);
+    /// An aarch64-specific identifier for the V12 register.
+    pub const V12: RegNum = RegNum(UNW_AARCH64_V12);
+    /// An aarch64-specific identifier for the V13 register.
+    pub const V13: RegNum = RegNum(UNW_AARCH64_V13);
+    /// An aarch64-specific identifier for the V14 register.
+    pub const V14: RegNum = RegNum(UNW_AARCH64_V14);
+    /// An aarch64-specific identifier for the V15 register.
+    pub const V15: RegNum = RegNum(UNW_AARCH64_V15);
+    /// An aarch64-specific identifier for the V16 register.
+    pub const V16: RegNum = RegNum(UNW_AARCH64_V16);
+    /// An aarch64-specific identifier for the V17 register.
+    pub const V17: RegNum = RegNum(UNW_AARCH64_V17);
+    /// An aarch64-specific identifier for the V18 register.
+    pub const V18: RegNum = RegNum(UNW_AARCH64_V18);
+    /// An aarch64-specific identifier for the V19 register.
+    pub const V19: RegNum = RegNum(UNW_AARCH64_V19);
+    /// An aarch64-specific identifier for the V20 register.
+    pub const V20: RegNum = RegNum(UNW_AARCH64_V20);
+    /// An aarch64-specific identifier for the V21 register.
+    pub const V21: RegNum = RegNum(UNW_AARCH64_V21);
+    /// An aarch64-specific identifier for the V22 register.
+    pub const V22: RegNum = RegNum(UNW_AARCH64_V22);
+    /// An aarch64-specific identifier for the V23 register.
+    pub const V23: RegNum = RegNum(UNW_AARCH64_V23);
+    /// An aarch64-specific identifier for the V24 register.
+    pub const V24: RegNum = RegNum(UNW_AARCH64_V24);
+    /// An aarch64-specific identifier for the V25 register.
+    pub const V25: RegNum = RegNum(UNW_AARCH64_V25);
+    /// An aarch64-specific identifier for the V26 register.
+    pub const V26: RegNum = RegNum(UNW_AARCH64_V26);
+    /// An aarch64-specific identifier for the V27 register.
+    pub const V27: RegNum = RegNum(UNW_AARCH64_V27);
+    /// An aarch64-specific identifier for the V28 register.
+    pub const V28: RegNum = RegNum(UNW_AARCH64_V28);
+    /// An aarch64-specific identifier for the V29 register.
+    pub const V29: RegNum = RegNum(UNW_AARCH64_V29);
+    /// An aarch64-specific identifier for the V30 register.
+    pub const V30: RegNum = RegNum(UNW_AARCH64_V30);
+    /// An aarch64-specific identifier for the V31 register.
+    pub const V31: RegNum = RegNum(UNW_AARCH64_V31);
+    /// An aarch64-specific identifier for the FPSR register.
+    pub const FPSR: RegNum = RegNum(UNW_AARCH64_FPSR);
+    /// An aarch64-specific identifier for the FPCR register.
+    pub const FPCR: RegNum = RegNum(UNW_AARCH64_FPCR);
+    /// An aarch64-specific identifier for the canonical frame address.
+    pub const CFA: RegNum = RegNum(UNW_AARCH64_CFA);
+}
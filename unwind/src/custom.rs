@@ -0,0 +1,350 @@
+use libc::{c_char, c_int, c_void, size_t};
+use std::any::Any;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+use unwind_sys::*;
+
+use crate::{Accessors, AddressSpaceRef, Error, ProcedureName, RegNum, Result};
+
+thread_local! {
+    static PANIC: RefCell<Option<Box<dyn Any + Send>>> = RefCell::new(None);
+}
+
+/// Resumes a panic raised by a `CustomAccessors` method while libunwind was calling into it.
+pub(crate) fn resume_panic() {
+    if let Some(payload) = PANIC.with(|p| p.borrow_mut().take()) {
+        panic::resume_unwind(payload);
+    }
+}
+
+fn catch<F>(f: F) -> c_int
+where
+    F: FnOnce() -> Result<()>,
+{
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => UNW_ESUCCESS,
+        Ok(Err(e)) => e.0,
+        Err(payload) => {
+            PANIC.with(|p| *p.borrow_mut() = Some(payload));
+            -UNW_EUNSPEC
+        }
+    }
+}
+
+/// A table of unwind information covering a range of code in an address space.
+///
+/// Tables are returned from [`CustomAccessors::find_proc_info`] and searched by libunwind for the
+/// procedure containing an instruction pointer.
+///
+/// [`CustomAccessors::find_proc_info`]: trait.CustomAccessors.html#method.find_proc_info
+#[derive(Copy, Clone)]
+pub struct UnwindTable(unw_dyn_info_t);
+
+impl UnwindTable {
+    /// Creates a table from the binary search table of an `.eh_frame_hdr` section.
+    ///
+    /// All addresses are in the address space being unwound. `start_ip` and `end_ip` bound the
+    /// code covered by the table, `segbase` is the address of the `.eh_frame_hdr` section,
+    /// `table_data` is the address of its search table, and `fde_count` is the number of entries
+    /// in the search table.
+    pub fn eh_frame_hdr(
+        start_ip: u64,
+        end_ip: u64,
+        segbase: u64,
+        table_data: u64,
+        fde_count: u64,
+    ) -> UnwindTable {
+        unsafe {
+            let mut info = mem::zeroed::<unw_dyn_info_t>();
+            info.start_ip = start_ip as unw_word_t;
+            info.end_ip = end_ip as unw_word_t;
+            info.format = UNW_INFO_FORMAT_REMOTE_TABLE;
+            // each search table entry is a pair of 32 bit offsets, and the length is in words
+            info.u.rti.table_len = (fde_count * 8 / mem::size_of::<unw_word_t>() as u64) as _;
+            info.u.rti.segbase = segbase as unw_word_t;
+            info.u.rti.table_data = table_data as unw_word_t;
+            UnwindTable(info)
+        }
+    }
+
+    /// Returns the starting address of the code covered by the table.
+    pub fn start_ip(&self) -> u64 {
+        self.0.start_ip as u64
+    }
+
+    /// Returns the ending address of the code covered by the table.
+    pub fn end_ip(&self) -> u64 {
+        self.0.end_ip as u64
+    }
+}
+
+/// A trait implemented by the state type of a custom address space.
+///
+/// libunwind calls into an address space's accessors to read the registers and memory of the
+/// thread being unwound and to locate unwind information. Implementing this trait for a type `T`
+/// allows [`Accessors::custom`] to build an `Accessors<T>`, and a reference to the state is then
+/// passed to [`Cursor::remote`].
+///
+/// Only the memory and register readers are required. The remaining methods default to reporting
+/// that no information is available, in which case libunwind falls back to heuristics such as
+/// frame pointer chains where the target architecture supports them.
+///
+/// Panics raised by these methods are caught before they reach libunwind, and are resumed when the
+/// `Cursor` method that triggered the call returns.
+///
+/// [`Accessors::custom`]: struct.Accessors.html#method.custom
+/// [`Cursor::remote`]: struct.Cursor.html#method.remote
+pub trait CustomAccessors: Sized {
+    /// Returns the table of unwind information covering the instruction pointer `ip`.
+    ///
+    /// `need_unwind_info` indicates if libunwind will use the unwind information of the procedure
+    /// or just its bounds.
+    fn find_proc_info(
+        &self,
+        address_space: &AddressSpaceRef<Self>,
+        ip: u64,
+        need_unwind_info: bool,
+    ) -> Result<UnwindTable> {
+        let _ = (address_space, ip, need_unwind_info);
+        Err(Error::NOINFO)
+    }
+
+    /// Returns the address of the dynamic unwind info list of the address space.
+    fn get_dyn_info_list_addr(&self, address_space: &AddressSpaceRef<Self>) -> Result<u64> {
+        let _ = address_space;
+        Err(Error::NOINFO)
+    }
+
+    /// Reads the word of memory at the specified address.
+    fn access_mem(&self, address_space: &AddressSpaceRef<Self>, addr: u64) -> Result<u64>;
+
+    /// Writes a word of memory to the specified address.
+    ///
+    /// Defaults to returning [`Error::INVAL`].
+    ///
+    /// [`Error::INVAL`]: struct.Error.html#associatedconstant.INVAL
+    fn write_mem(
+        &self,
+        address_space: &AddressSpaceRef<Self>,
+        addr: u64,
+        value: u64,
+    ) -> Result<()> {
+        let _ = (address_space, addr, value);
+        Err(Error::INVAL)
+    }
+
+    /// Reads the value of a register of the thread being unwound.
+    fn access_reg(&self, address_space: &AddressSpaceRef<Self>, reg: RegNum) -> Result<u64>;
+
+    /// Writes the value of a register of the thread being unwound.
+    ///
+    /// Defaults to returning [`Error::READONLYREG`].
+    ///
+    /// [`Error::READONLYREG`]: struct.Error.html#associatedconstant.READONLYREG
+    fn write_reg(
+        &self,
+        address_space: &AddressSpaceRef<Self>,
+        reg: RegNum,
+        value: u64,
+    ) -> Result<()> {
+        let _ = (address_space, reg, value);
+        Err(Error::READONLYREG)
+    }
+
+    /// Reads the raw value of a floating point or vector register of the thread being unwound.
+    ///
+    /// `value` is [`RegNum::FP_REGISTER_SIZE`] bytes long, and is filled in with the same layout
+    /// as is returned by [`Cursor::fp_register`]. libunwind never calls this method on x86_64.
+    ///
+    /// Defaults to returning [`Error::BADREG`].
+    ///
+    /// [`RegNum::FP_REGISTER_SIZE`]: struct.RegNum.html#associatedconstant.FP_REGISTER_SIZE
+    /// [`Cursor::fp_register`]: struct.Cursor.html#method.fp_register
+    /// [`Error::BADREG`]: struct.Error.html#associatedconstant.BADREG
+    fn access_fpreg(
+        &self,
+        address_space: &AddressSpaceRef<Self>,
+        reg: RegNum,
+        value: &mut [u8],
+    ) -> Result<()> {
+        let _ = (address_space, reg, value);
+        Err(Error::BADREG)
+    }
+
+    /// Returns the name of the procedure containing the specified address.
+    fn get_proc_name(
+        &self,
+        address_space: &AddressSpaceRef<Self>,
+        addr: u64,
+    ) -> Result<ProcedureName> {
+        let _ = (address_space, addr);
+        Err(Error::NOINFO)
+    }
+}
+
+impl<T> Accessors<T>
+where
+    T: CustomAccessors,
+{
+    /// Returns `Accessors` which call into the `CustomAccessors` implementation of `T`.
+    pub fn custom() -> Accessors<T> {
+        let accessors = unw_accessors_t {
+            find_proc_info: Some(find_proc_info::<T>),
+            put_unwind_info: Some(put_unwind_info),
+            get_dyn_info_list_addr: Some(get_dyn_info_list_addr::<T>),
+            access_mem: Some(access_mem::<T>),
+            access_reg: Some(access_reg::<T>),
+            access_fpreg: Some(access_fpreg::<T>),
+            resume: Some(resume),
+            get_proc_name: Some(get_proc_name::<T>),
+        };
+        Accessors(accessors, PhantomData)
+    }
+}
+
+unsafe fn args<'a, T>(asp: unw_addr_space_t, arg: *mut c_void) -> (&'a AddressSpaceRef<T>, &'a T) {
+    (&*(asp as *const AddressSpaceRef<T>), &*(arg as *const T))
+}
+
+unsafe extern "C" fn find_proc_info<T>(
+    asp: unw_addr_space_t,
+    ip: unw_word_t,
+    pip: *mut unw_proc_info_t,
+    need_unwind_info: c_int,
+    arg: *mut c_void,
+) -> c_int
+where
+    T: CustomAccessors,
+{
+    catch(|| {
+        let (address_space, state) = args::<T>(asp, arg);
+        let mut table = state.find_proc_info(address_space, ip as u64, need_unwind_info != 0)?;
+        let ret = dwarf_search_unwind_table(asp, ip, &mut table.0, pip, need_unwind_info, arg);
+        if ret < 0 {
+            Err(Error(ret))
+        } else {
+            Ok(())
+        }
+    })
+}
+
+// unwind info found via dwarf_search_unwind_table is owned and released by libunwind itself
+unsafe extern "C" fn put_unwind_info(_: unw_addr_space_t, _: *mut unw_proc_info_t, _: *mut c_void) {
+}
+
+unsafe extern "C" fn access_fpreg<T>(
+    asp: unw_addr_space_t,
+    regnum: unw_regnum_t,
+    fpvalp: *mut unw_fpreg_t,
+    write: c_int,
+    arg: *mut c_void,
+) -> c_int
+where
+    T: CustomAccessors,
+{
+    catch(|| {
+        if write != 0 {
+            return Err(Error::READONLYREG);
+        }
+
+        let (address_space, state) = args::<T>(asp, arg);
+        state.access_fpreg(address_space, RegNum(regnum), &mut (*fpvalp).bytes)
+    })
+}
+
+unsafe extern "C" fn resume(_: unw_addr_space_t, _: *mut unw_cursor_t, _: *mut c_void) -> c_int {
+    -UNW_EINVAL
+}
+
+unsafe extern "C" fn get_dyn_info_list_addr<T>(
+    asp: unw_addr_space_t,
+    dilap: *mut unw_word_t,
+    arg: *mut c_void,
+) -> c_int
+where
+    T: CustomAccessors,
+{
+    catch(|| {
+        let (address_space, state) = args::<T>(asp, arg);
+        *dilap = state.get_dyn_info_list_addr(address_space)? as unw_word_t;
+        Ok(())
+    })
+}
+
+unsafe extern "C" fn access_mem<T>(
+    asp: unw_addr_space_t,
+    addr: unw_word_t,
+    valp: *mut unw_word_t,
+    write: c_int,
+    arg: *mut c_void,
+) -> c_int
+where
+    T: CustomAccessors,
+{
+    catch(|| {
+        let (address_space, state) = args::<T>(asp, arg);
+        if write == 0 {
+            *valp = state.access_mem(address_space, addr as u64)? as unw_word_t;
+            Ok(())
+        } else {
+            state.write_mem(address_space, addr as u64, *valp as u64)
+        }
+    })
+}
+
+unsafe extern "C" fn access_reg<T>(
+    asp: unw_addr_space_t,
+    regnum: unw_regnum_t,
+    valp: *mut unw_word_t,
+    write: c_int,
+    arg: *mut c_void,
+) -> c_int
+where
+    T: CustomAccessors,
+{
+    catch(|| {
+        let (address_space, state) = args::<T>(asp, arg);
+        if write == 0 {
+            *valp = state.access_reg(address_space, RegNum(regnum))? as unw_word_t;
+            Ok(())
+        } else {
+            state.write_reg(address_space, RegNum(regnum), *valp as u64)
+        }
+    })
+}
+
+unsafe extern "C" fn get_proc_name<T>(
+    asp: unw_addr_space_t,
+    addr: unw_word_t,
+    bufp: *mut c_char,
+    buf_len: size_t,
+    offp: *mut unw_word_t,
+    arg: *mut c_void,
+) -> c_int
+where
+    T: CustomAccessors,
+{
+    catch(|| {
+        let (address_space, state) = args::<T>(asp, arg);
+        let name = state.get_proc_name(address_space, addr as u64)?;
+        *offp = name.offset() as unw_word_t;
+
+        if buf_len == 0 {
+            return Err(Error::NOMEM);
+        }
+        let buf = slice::from_raw_parts_mut(bufp as *mut u8, buf_len);
+        let name = name.name().as_bytes();
+        // match libunwind's behavior of returning the truncated, null-terminated name on overflow
+        let len = name.len().min(buf.len() - 1);
+        buf[..len].copy_from_slice(&name[..len]);
+        buf[len] = 0;
+        if len < name.len() {
+            Err(Error::NOMEM)
+        } else {
+            Ok(())
+        }
+    })
+}
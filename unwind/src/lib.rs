@@ -48,12 +48,20 @@ use std::ffi::CStr;
 use std::fmt;
 use std::marker::PhantomData;
 use std::marker::PhantomPinned;
-use std::mem::MaybeUninit;
+use std::mem::{self, MaybeUninit};
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
 use std::result;
 use unwind_sys::*;
 
+pub use crate::custom::{CustomAccessors, UnwindTable};
+pub use crate::maps::MapsEntry;
+pub use crate::snapshot::Snapshot;
+
+mod custom;
+mod maps;
+mod snapshot;
+
 #[doc(hidden)]
 pub mod private {
     pub use std::mem::MaybeUninit;
@@ -160,6 +168,12 @@ impl PTraceState {
 }
 
 /// A collection of functions used to unwind an arbitrary process.
+///
+/// Accessors for a custom address space can be created from a type implementing
+/// [`CustomAccessors`] with [`Accessors::custom`].
+///
+/// [`CustomAccessors`]: trait.CustomAccessors.html
+/// [`Accessors::custom`]: #method.custom
 pub struct Accessors<T>(unw_accessors_t, PhantomData<T>);
 
 #[cfg(feature = "ptrace")]
@@ -223,10 +237,20 @@ impl<T> AddressSpaceRef<T> {
     fn as_ptr(&self) -> unw_addr_space_t {
         self as *const _ as *mut _
     }
+
+    /// Flushes all cached unwind information.
+    ///
+    /// This should be called if the code mapped into the address space may have changed, for example due to a shared
+    /// library being unloaded.
+    pub fn flush_cache(&mut self) {
+        unsafe {
+            unw_flush_cache(self.as_ptr(), 0, 0);
+        }
+    }
 }
 
 /// An identifier of a processor register.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct RegNum(c_int);
 
 impl RegNum {
@@ -235,17 +259,62 @@ impl RegNum {
 
     /// A generic identifier for the register storing the stack pointer.
     pub const SP: RegNum = RegNum(UNW_REG_SP);
+
+    /// The size in bytes of the values of floating point and vector registers on the target
+    /// architecture.
+    pub const FP_REGISTER_SIZE: usize = mem::size_of::<unw_fpreg_t>();
+
+    /// Determines if the register is a floating point or vector register.
+    ///
+    /// The values of these registers are read with `Cursor::fp_register` rather than
+    /// `Cursor::register`.
+    pub fn is_fp_register(&self) -> bool {
+        unsafe { unw_tdep_is_fpreg(self.0) != 0 }
+    }
 }
 
 #[cfg(not(pre16))]
 #[cfg(target_arch = "x86_64")]
 mod x86_64;
 
+#[cfg(target_arch = "aarch64")]
+mod aarch64;
+
+/// The format of a procedure's unwind information.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UnwindInfoFormat(c_int);
+
+impl UnwindInfoFormat {
+    /// Dynamic unwind information registered with libunwind at runtime.
+    ///
+    /// This is also reported for procedures without any unwind information, which are unwound
+    /// with heuristics such as following frame pointers.
+    pub const DYNAMIC: UnwindInfoFormat = UnwindInfoFormat(UNW_INFO_FORMAT_DYNAMIC);
+
+    /// DWARF call frame information from a local unwind table.
+    pub const TABLE: UnwindInfoFormat = UnwindInfoFormat(UNW_INFO_FORMAT_TABLE);
+
+    /// DWARF call frame information from an unwind table in a remote address space.
+    pub const REMOTE_TABLE: UnwindInfoFormat = UnwindInfoFormat(UNW_INFO_FORMAT_REMOTE_TABLE);
+
+    /// ARM exception index table entries.
+    pub const ARM_EXIDX: UnwindInfoFormat = UnwindInfoFormat(UNW_INFO_FORMAT_ARM_EXIDX);
+
+    /// DWARF call frame information from an unwind table using instruction pointer offsets.
+    pub const IP_OFFSET: UnwindInfoFormat = UnwindInfoFormat(UNW_INFO_FORMAT_IP_OFFSET);
+}
+
 /// Information about a procedure.
 #[derive(Copy, Clone)]
 pub struct ProcedureInfo {
     start_ip: u64,
     end_ip: u64,
+    lsda: u64,
+    handler: u64,
+    gp: u64,
+    flags: u64,
+    format: UnwindInfoFormat,
+    unwind_info_size: usize,
 }
 
 impl ProcedureInfo {
@@ -258,6 +327,40 @@ impl ProcedureInfo {
     pub fn end_ip(&self) -> u64 {
         self.end_ip
     }
+
+    /// Returns the address of the procedure's language-specific data area, or 0 if it has none.
+    ///
+    /// The LSDA describes the landing pads of the procedure.
+    pub fn lsda(&self) -> u64 {
+        self.lsda
+    }
+
+    /// Returns the address of the procedure's personality routine, or 0 if it has none.
+    pub fn handler(&self) -> u64 {
+        self.handler
+    }
+
+    /// Returns the global pointer value of the procedure.
+    ///
+    /// This is only meaningful on architectures which use a global pointer.
+    pub fn gp(&self) -> u64 {
+        self.gp
+    }
+
+    /// Returns the architecture-specific flags of the procedure.
+    pub fn flags(&self) -> u64 {
+        self.flags
+    }
+
+    /// Returns the format of the procedure's unwind information.
+    pub fn format(&self) -> UnwindInfoFormat {
+        self.format
+    }
+
+    /// Returns the size of the procedure's unwind information in bytes.
+    pub fn unwind_info_size(&self) -> usize {
+        self.unwind_info_size
+    }
 }
 
 /// The name of a procedure.
@@ -268,6 +371,15 @@ pub struct ProcedureName {
 }
 
 impl ProcedureName {
+    /// Creates a new `ProcedureName`.
+    ///
+    /// This is intended for use by [`CustomAccessors::get_proc_name`] implementations.
+    ///
+    /// [`CustomAccessors::get_proc_name`]: trait.CustomAccessors.html#method.get_proc_name
+    pub fn new(name: String, offset: u64) -> ProcedureName {
+        ProcedureName { name, offset }
+    }
+
     /// Returns the name of the procedure.
     pub fn name(&self) -> &str {
         &self.name
@@ -280,6 +392,18 @@ impl ProcedureName {
     }
 }
 
+/// The location at which the value of a register in a stack frame was saved.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SaveLocation {
+    /// The register was not saved, either because it still holds its value or because the
+    /// unwinder doesn't know where it was saved.
+    None,
+    /// The register was saved in memory at the specified address.
+    Memory(u64),
+    /// The register was saved in another register.
+    Register(RegNum),
+}
+
 /// A snapshot of the machine-state of a process.
 ///
 /// A pinned context can be created with the `get_context!` macro.
@@ -342,6 +466,7 @@ impl<'a> Cursor<'a> {
                 address_space.as_ptr(),
                 state as *const T as *mut c_void,
             );
+            custom::resume_panic();
             if ret == UNW_ESUCCESS {
                 Ok(Cursor(cursor.assume_init(), PhantomData))
             } else {
@@ -362,6 +487,7 @@ impl<'a> Cursor<'a> {
             }
 
             let ret = unw_step(&mut self.0);
+            custom::resume_panic();
             if ret > 0 {
                 Ok(true)
             } else if ret == 0 {
@@ -379,6 +505,7 @@ impl<'a> Cursor<'a> {
         unsafe {
             let mut val = 0;
             let ret = unw_get_reg(&self.0 as *const _ as *mut _, num.0, &mut val);
+            custom::resume_panic();
             if ret == UNW_ESUCCESS {
                 Ok(val as u64)
             } else {
@@ -387,16 +514,98 @@ impl<'a> Cursor<'a> {
         }
     }
 
+    /// Returns the raw value of a floating point or vector register at the current frame.
+    ///
+    /// The value is [`RegNum::FP_REGISTER_SIZE`] bytes long. On aarch64 it holds the full 128 bit
+    /// vector register, and on x86 it's an 80 bit x87 extended precision value padded to 12 bytes.
+    ///
+    /// libunwind does not support floating point registers on x86_64, so this always returns an
+    /// error there.
+    ///
+    /// [`RegNum::FP_REGISTER_SIZE`]: struct.RegNum.html#associatedconstant.FP_REGISTER_SIZE
+    pub fn fp_register(&mut self, num: RegNum) -> Result<Vec<u8>> {
+        unsafe {
+            let mut val = MaybeUninit::<unw_fpreg_t>::zeroed();
+            let ret = unw_get_fpreg(&self.0 as *const _ as *mut _, num.0, val.as_mut_ptr());
+            custom::resume_panic();
+            if ret == UNW_ESUCCESS {
+                Ok(val.assume_init().bytes.to_vec())
+            } else {
+                Err(Error(ret))
+            }
+        }
+    }
+
+    /// Sets the value of an integral register at the current frame.
+    ///
+    /// Writing a register of an older frame updates the location it was saved to, so the new value
+    /// will be seen by the frame once execution returns to it.
+    pub fn set_register(&mut self, num: RegNum, value: u64) -> Result<()> {
+        unsafe {
+            let ret = unw_set_reg(&mut self.0, num.0, value as unw_word_t);
+            custom::resume_panic();
+            if ret == UNW_ESUCCESS {
+                Ok(())
+            } else {
+                Err(Error(ret))
+            }
+        }
+    }
+
+    /// Returns the location at which the value of a register at the current frame was saved.
+    pub fn save_location(&mut self, num: RegNum) -> Result<SaveLocation> {
+        unsafe {
+            let mut loc = MaybeUninit::<unw_save_loc_t>::uninit();
+            let ret = unw_get_save_loc(&mut self.0, num.0, loc.as_mut_ptr());
+            custom::resume_panic();
+            if ret != UNW_ESUCCESS {
+                return Err(Error(ret));
+            }
+
+            let loc = loc.assume_init();
+            let loc = match loc.type_ {
+                unw_save_loc_type_t::UNW_SLT_NONE => SaveLocation::None,
+                unw_save_loc_type_t::UNW_SLT_MEMORY => SaveLocation::Memory(loc.u.addr as u64),
+                unw_save_loc_type_t::UNW_SLT_REG => SaveLocation::Register(RegNum(loc.u.regnum)),
+            };
+            Ok(loc)
+        }
+    }
+
+    /// Resumes execution at the current frame.
+    ///
+    /// Execution continues at the frame's instruction pointer with its register state, including
+    /// any values written with `set_register`. For a remote address space, the accessors' resume
+    /// function is used, which is not supported by custom accessors. This method only returns on
+    /// error.
+    ///
+    /// # Safety
+    ///
+    /// All frames newer than the current frame are discarded without running destructors, and
+    /// the register state of the current frame must be valid for the code being resumed.
+    pub unsafe fn resume(&mut self) -> Error {
+        let ret = unw_resume(&mut self.0);
+        custom::resume_panic();
+        Error(ret)
+    }
+
     /// Returns information about the procedure at the current frame.
     pub fn procedure_info(&mut self) -> Result<ProcedureInfo> {
         unsafe {
             let mut info = MaybeUninit::uninit();
             let ret = unw_get_proc_info(&self.0 as *const _ as *mut _, info.as_mut_ptr());
+            custom::resume_panic();
             if ret == UNW_ESUCCESS {
                 let info = info.assume_init();
                 Ok(ProcedureInfo {
                     start_ip: info.start_ip as u64,
                     end_ip: info.end_ip as u64,
+                    lsda: info.lsda as u64,
+                    handler: info.handler as u64,
+                    gp: info.gp as u64,
+                    flags: info.flags as u64,
+                    format: UnwindInfoFormat(info.format),
+                    unwind_info_size: info.unwind_info_size as usize,
                 })
             } else {
                 Err(Error(ret))
@@ -430,6 +639,7 @@ impl<'a> Cursor<'a> {
                 buf.len(),
                 &mut raw_off,
             );
+            custom::resume_panic();
             *offset = raw_off as u64;
             if ret == UNW_ESUCCESS {
                 Ok(())
@@ -473,6 +683,7 @@ impl<'a> Cursor<'a> {
     pub fn is_signal_frame(&mut self) -> Result<bool> {
         unsafe {
             let ret = unw_is_signal_frame(&self.0 as *const _ as *mut _);
+            custom::resume_panic();
             if ret < 0 {
                 Err(Error(ret))
             } else {
@@ -0,0 +1,150 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// An entry of a process's `/proc/<pid>/maps` file, describing one of its memory mappings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapsEntry {
+    start: u64,
+    end: u64,
+    executable: bool,
+    offset: u64,
+    path: Option<PathBuf>,
+}
+
+impl MapsEntry {
+    /// Reads the memory mappings of a process.
+    pub fn read(pid: u32) -> io::Result<Vec<MapsEntry>> {
+        let maps = fs::read_to_string(format!("/proc/{}/maps", pid))?;
+        Ok(maps.lines().filter_map(MapsEntry::parse).collect())
+    }
+
+    // 7f2c3c1e5000-7f2c3c1e7000 r-xp 00002000 fd:01 1234                       /usr/lib/libfoo.so
+    fn parse(line: &str) -> Option<MapsEntry> {
+        let mut it = line.splitn(6, ' ');
+        let mut range = it.next()?.splitn(2, '-');
+        let start = u64::from_str_radix(range.next()?, 16).ok()?;
+        let end = u64::from_str_radix(range.next()?, 16).ok()?;
+        let perms = it.next()?;
+        let offset = u64::from_str_radix(it.next()?, 16).ok()?;
+        // the path is padded to a fixed column, and is missing entirely from some anonymous mappings
+        let path = it.nth(2).unwrap_or("").trim_start();
+
+        // files which have been deleted or replaced since they were mapped can't be read from their path
+        let path = if path.starts_with('/') && !path.ends_with(" (deleted)") {
+            Some(PathBuf::from(path))
+        } else {
+            None
+        };
+
+        Some(MapsEntry {
+            start,
+            end,
+            executable: perms.contains('x'),
+            offset,
+            path,
+        })
+    }
+
+    /// Returns the starting address of the mapping.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// Returns the ending address of the mapping.
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Determines if the mapping is executable.
+    pub fn is_executable(&self) -> bool {
+        self.executable
+    }
+
+    /// Returns the offset into the backing file at which the mapping starts.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the path of the file backing the mapping.
+    ///
+    /// Returns `None` for anonymous mappings, special mappings like the stack and vDSO, and files
+    /// which have been deleted since they were mapped.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_file() {
+        let entry = MapsEntry::parse(
+            "7f2c3c1e5000-7f2c3c1e7000 r-xp 00002000 fd:01 1234                       /usr/lib/libfoo.so",
+        )
+        .unwrap();
+        assert_eq!(
+            entry,
+            MapsEntry {
+                start: 0x7f2c3c1e5000,
+                end: 0x7f2c3c1e7000,
+                executable: true,
+                offset: 0x2000,
+                path: Some(PathBuf::from("/usr/lib/libfoo.so")),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_not_executable() {
+        let entry =
+            MapsEntry::parse("00400000-00452000 r--p 00000000 08:02 173521     /usr/bin/foo")
+                .unwrap();
+        assert!(!entry.is_executable());
+        assert_eq!(entry.path(), Some(Path::new("/usr/bin/foo")));
+    }
+
+    #[test]
+    fn parse_path_with_spaces() {
+        let entry =
+            MapsEntry::parse("00400000-00452000 r-xp 00001000 08:02 173521 /opt/my app/bin  2")
+                .unwrap();
+        assert_eq!(entry.offset(), 0x1000);
+        assert_eq!(entry.path(), Some(Path::new("/opt/my app/bin  2")));
+    }
+
+    #[test]
+    fn parse_deleted() {
+        let entry = MapsEntry::parse(
+            "7f2c3c1e5000-7f2c3c1e7000 r-xp 00002000 fd:01 1234 /usr/lib/libfoo.so (deleted)",
+        )
+        .unwrap();
+        assert_eq!(entry.path(), None);
+    }
+
+    #[test]
+    fn parse_anonymous() {
+        let entry = MapsEntry::parse("7f2c3c1e8000-7f2c3c1e9000 rw-p 00000000 00:00 0 ").unwrap();
+        assert_eq!(entry.start(), 0x7f2c3c1e8000);
+        assert_eq!(entry.end(), 0x7f2c3c1e9000);
+        assert_eq!(entry.path(), None);
+
+        let entry = MapsEntry::parse("7f2c3c1e8000-7f2c3c1e9000 rw-p 00000000 00:00 0").unwrap();
+        assert_eq!(entry.path(), None);
+
+        let entry = MapsEntry::parse(
+            "7ffd4c3d3000-7ffd4c3f4000 rw-p 00000000 00:00 0                          [stack]",
+        )
+        .unwrap();
+        assert_eq!(entry.path(), None);
+    }
+
+    #[test]
+    fn parse_malformed() {
+        assert!(MapsEntry::parse("").is_none());
+        assert!(MapsEntry::parse("7f2c3c1e8000 rw-p 00000000 00:00 0").is_none());
+        assert!(MapsEntry::parse("7f2c3c1e8000-7f2c3c1e9000 rw-p").is_none());
+    }
+}
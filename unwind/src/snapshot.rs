@@ -0,0 +1,338 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::mem;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use unwind_sys::unw_word_t;
+
+use crate::{AddressSpaceRef, CustomAccessors, Error, MapsEntry, RegNum, Result, UnwindTable};
+
+const PT_GNU_EH_FRAME: u32 = 0x6474_e550;
+
+const DW_EH_PE_OMIT: u8 = 0xff;
+const DW_EH_PE_DATAREL_SDATA4: u8 = 0x3b;
+
+/// A captured register set and copies of a thread's memory which can be unwound after the fact.
+///
+/// Unwinding a `Snapshot` does not require the thread or process it was captured from to still be
+/// stopped, or even to still exist. Memory reads are first served from the captured regions, and
+/// then from the files backing the process's mappings, which covers the code and unwind tables
+/// of executables and shared libraries.
+///
+/// A `Snapshot` implements [`CustomAccessors`], so it can be used with [`Accessors::custom`] and
+/// [`Cursor::remote`].
+///
+/// [`CustomAccessors`]: trait.CustomAccessors.html
+/// [`Accessors::custom`]: struct.Accessors.html#method.custom
+/// [`Cursor::remote`]: struct.Cursor.html#method.remote
+#[derive(Default)]
+pub struct Snapshot {
+    registers: HashMap<RegNum, u64>,
+    regions: Vec<Region>,
+    mappings: Vec<Mapping>,
+    files: RefCell<HashMap<PathBuf, Option<Rc<ElfFile>>>>,
+}
+
+struct Region {
+    address: u64,
+    data: Vec<u8>,
+}
+
+struct Mapping {
+    start: u64,
+    end: u64,
+    offset: u64,
+    path: PathBuf,
+}
+
+struct ElfFile {
+    file: File,
+    eh_frame_hdr: Option<EhFrameHdr>,
+}
+
+struct EhFrameHdr {
+    offset: u64,
+    table_offset: u64,
+    fde_count: u64,
+}
+
+impl Snapshot {
+    /// Creates a new, empty `Snapshot`.
+    pub fn new() -> Snapshot {
+        Snapshot::default()
+    }
+
+    /// Sets the value of a register at the point the snapshot was captured.
+    pub fn register(&mut self, reg: RegNum, value: u64) -> &mut Snapshot {
+        self.registers.insert(reg, value);
+        self
+    }
+
+    /// Adds a copy of the memory starting at the specified address, such as the top of a thread's
+    /// stack.
+    pub fn memory(&mut self, address: u64, data: Vec<u8>) -> &mut Snapshot {
+        self.regions.push(Region { address, data });
+        self
+    }
+
+    /// Adds a file-backed mapping of the captured process.
+    ///
+    /// `offset` is the offset into the file at which the mapping starts.
+    pub fn mapping(&mut self, start: u64, end: u64, offset: u64, path: &Path) -> &mut Snapshot {
+        self.mappings.push(Mapping {
+            start,
+            end,
+            offset,
+            path: path.to_path_buf(),
+        });
+        self
+    }
+
+    /// Adds all file-backed mappings of a process, as listed in `/proc/<pid>/maps`.
+    ///
+    /// This should be called when the snapshot is captured, since the mappings of the process may
+    /// change afterwards. Mappings of files which have been deleted since they were mapped are
+    /// skipped, since the file now at their path may not have the same contents.
+    pub fn mappings(&mut self, pid: u32) -> io::Result<&mut Snapshot> {
+        for entry in MapsEntry::read(pid)? {
+            if let Some(path) = entry.path() {
+                self.mapping(entry.start(), entry.end(), entry.offset(), path);
+            }
+        }
+        Ok(self)
+    }
+
+    fn read_region(&self, addr: u64, buf: &mut [u8]) -> bool {
+        for region in &self.regions {
+            if addr < region.address {
+                continue;
+            }
+            // addresses read from a corrupt stack can be anything, so the end of the read can overflow
+            let start = (addr - region.address) as usize;
+            let end = match start.checked_add(buf.len()) {
+                Some(end) => end,
+                None => continue,
+            };
+            if let Some(data) = region.data.get(start..end) {
+                buf.copy_from_slice(data);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn find_mapping(&self, addr: u64) -> Option<&Mapping> {
+        self.mappings
+            .iter()
+            .find(|m| m.start <= addr && addr < m.end)
+    }
+
+    fn elf_file(&self, path: &Path) -> Option<Rc<ElfFile>> {
+        self.files
+            .borrow_mut()
+            .entry(path.to_path_buf())
+            .or_insert_with(|| ElfFile::open(path).ok().map(Rc::new))
+            .clone()
+    }
+
+    fn read_mapping(&self, addr: u64, buf: &mut [u8]) -> bool {
+        let mapping = match self.find_mapping(addr) {
+            Some(mapping) => mapping,
+            None => return false,
+        };
+        let file = match self.elf_file(&mapping.path) {
+            Some(file) => file,
+            None => return false,
+        };
+
+        file.file
+            .read_exact_at(buf, addr - mapping.start + mapping.offset)
+            .is_ok()
+    }
+
+    // finds the address at which a file offset is mapped, in any mapping of the same file
+    fn file_address(&self, path: &Path, offset: u64) -> Option<u64> {
+        self.mappings
+            .iter()
+            .find(|m| m.path == path && m.offset <= offset && offset - m.offset < m.end - m.start)
+            .map(|m| m.start + offset - m.offset)
+    }
+}
+
+impl CustomAccessors for Snapshot {
+    fn find_proc_info(
+        &self,
+        _: &AddressSpaceRef<Snapshot>,
+        ip: u64,
+        _: bool,
+    ) -> Result<UnwindTable> {
+        let mapping = self.find_mapping(ip).ok_or(Error::NOINFO)?;
+        let file = self.elf_file(&mapping.path).ok_or(Error::NOINFO)?;
+        let hdr = file.eh_frame_hdr.as_ref().ok_or(Error::NOINFO)?;
+
+        let segbase = self
+            .file_address(&mapping.path, hdr.offset)
+            .ok_or(Error::NOINFO)?;
+        Ok(UnwindTable::eh_frame_hdr(
+            mapping.start,
+            mapping.end,
+            segbase,
+            segbase + hdr.table_offset,
+            hdr.fde_count,
+        ))
+    }
+
+    fn access_mem(&self, _: &AddressSpaceRef<Snapshot>, addr: u64) -> Result<u64> {
+        let mut buf = [0; mem::size_of::<unw_word_t>()];
+        if self.read_region(addr, &mut buf) || self.read_mapping(addr, &mut buf) {
+            Ok(unw_word_t::from_ne_bytes(buf) as u64)
+        } else {
+            Err(Error::INVAL)
+        }
+    }
+
+    fn access_reg(&self, _: &AddressSpaceRef<Snapshot>, reg: RegNum) -> Result<u64> {
+        self.registers.get(&reg).copied().ok_or(Error::BADREG)
+    }
+}
+
+impl ElfFile {
+    fn open(path: &Path) -> io::Result<ElfFile> {
+        let file = File::open(path)?;
+        let eh_frame_hdr = find_eh_frame_hdr(&file)?;
+        Ok(ElfFile { file, eh_frame_hdr })
+    }
+}
+
+fn read_u16(file: &File, offset: u64) -> io::Result<u16> {
+    let mut buf = [0; 2];
+    file.read_exact_at(&mut buf, offset)?;
+    Ok(u16::from_ne_bytes(buf))
+}
+
+fn read_u32(file: &File, offset: u64) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    file.read_exact_at(&mut buf, offset)?;
+    Ok(u32::from_ne_bytes(buf))
+}
+
+fn read_u64(file: &File, offset: u64) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    file.read_exact_at(&mut buf, offset)?;
+    Ok(u64::from_ne_bytes(buf))
+}
+
+// The snapshot is unwound on the machine it was captured on, so the ELF files are in native byte
+// order.
+fn find_eh_frame_hdr(file: &File) -> io::Result<Option<EhFrameHdr>> {
+    let mut ident = [0; 16];
+    file.read_exact_at(&mut ident, 0)?;
+    if ident[..4] != b"\x7fELF"[..] {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an ELF file",
+        ));
+    }
+
+    let (phoff, phentsize, phnum) = match ident[4] {
+        // ELFCLASS32
+        1 => (
+            read_u32(file, 0x1c)? as u64,
+            read_u16(file, 0x2a)?,
+            read_u16(file, 0x2c)?,
+        ),
+        // ELFCLASS64
+        2 => (
+            read_u64(file, 0x20)?,
+            read_u16(file, 0x36)?,
+            read_u16(file, 0x38)?,
+        ),
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "bad ELF class")),
+    };
+
+    for i in 0..phnum as u64 {
+        let phdr = phoff + i * phentsize as u64;
+        if read_u32(file, phdr)? != PT_GNU_EH_FRAME {
+            continue;
+        }
+
+        let offset = match ident[4] {
+            1 => read_u32(file, phdr + 4)? as u64,
+            _ => read_u64(file, phdr + 8)?,
+        };
+        return parse_eh_frame_hdr(file, offset);
+    }
+
+    Ok(None)
+}
+
+fn parse_eh_frame_hdr(file: &File, offset: u64) -> io::Result<Option<EhFrameHdr>> {
+    let mut header = [0; 4];
+    file.read_exact_at(&mut header, offset)?;
+    let [version, eh_frame_ptr_enc, fde_count_enc, table_enc] = header;
+
+    // libunwind only supports binary search tables of 32 bit data-relative offsets
+    if version != 1 || table_enc != DW_EH_PE_DATAREL_SDATA4 {
+        return Ok(None);
+    }
+
+    let eh_frame_ptr_size = match encoded_size(eh_frame_ptr_enc) {
+        Some(size) => size,
+        None => return Ok(None),
+    };
+    let fde_count_offset = 4 + eh_frame_ptr_size;
+    let fde_count = match fde_count_enc & 0x0f {
+        // DW_EH_PE_udata4 and DW_EH_PE_sdata4
+        0x03 | 0x0b => read_u32(file, offset + fde_count_offset)? as u64,
+        // DW_EH_PE_udata8 and DW_EH_PE_sdata8
+        0x04 | 0x0c => read_u64(file, offset + fde_count_offset)?,
+        _ => return Ok(None),
+    };
+    let table_offset = fde_count_offset + encoded_size(fde_count_enc).unwrap_or(0);
+
+    Ok(Some(EhFrameHdr {
+        offset,
+        table_offset,
+        fde_count,
+    }))
+}
+
+fn encoded_size(enc: u8) -> Option<u64> {
+    if enc == DW_EH_PE_OMIT {
+        return Some(0);
+    }
+
+    match enc & 0x0f {
+        // DW_EH_PE_absptr
+        0x00 => Some(mem::size_of::<unw_word_t>() as u64),
+        // DW_EH_PE_udata2 and DW_EH_PE_sdata2
+        0x02 | 0x0a => Some(2),
+        // DW_EH_PE_udata4 and DW_EH_PE_sdata4
+        0x03 | 0x0b => Some(4),
+        // DW_EH_PE_udata8 and DW_EH_PE_sdata8
+        0x04 | 0x0c => Some(8),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn read_region() {
+        let mut snapshot = Snapshot::new();
+        snapshot.memory(0x1000, (0..16).collect());
+
+        let mut buf = [0; 4];
+        assert!(snapshot.read_region(0x1004, &mut buf));
+        assert_eq!(buf, [4, 5, 6, 7]);
+        assert!(!snapshot.read_region(0xffc, &mut buf));
+        assert!(!snapshot.read_region(0x100d, &mut buf));
+        assert!(!snapshot.read_region(u64::MAX - 2, &mut buf));
+    }
+}
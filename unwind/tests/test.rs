@@ -1,4 +1,7 @@
-use unwind::{get_context, Cursor, RegNum};
+use unwind::{
+    get_context, Accessors, AddressSpace, AddressSpaceRef, Byteorder, Cursor, CustomAccessors,
+    Error, RegNum, Result,
+};
 
 #[test]
 fn local() {
@@ -94,6 +97,96 @@ fn x86_64() {
     foo();
 }
 
+#[test]
+#[cfg(target_arch = "aarch64")]
+fn aarch64() {
+    macro_rules! dump_register {
+        ($cursor:ident, $reg_num:expr, $reg_name:literal) => {
+            if let Ok(reg_val) = $cursor.register($reg_num) {
+                println!("{}: {:#016x}", $reg_name, reg_val);
+            } else {
+                println!("{} not stored!", $reg_name);
+            }
+        };
+    }
+
+    fn bar() {
+        get_context!(context);
+        let mut cursor = Cursor::local(context).unwrap();
+        let mut frames = vec![];
+
+        loop {
+            if let Ok(procedure_name) = cursor.procedure_name() {
+                println!("{}:", procedure_name.name());
+            } else {
+                println!("unknown:")
+            }
+
+            dump_register!(cursor, RegNum::X0, "x0");
+            dump_register!(cursor, RegNum::X1, "x1");
+            dump_register!(cursor, RegNum::X2, "x2");
+            dump_register!(cursor, RegNum::X3, "x3");
+            dump_register!(cursor, RegNum::X4, "x4");
+            dump_register!(cursor, RegNum::X5, "x5");
+            dump_register!(cursor, RegNum::X6, "x6");
+            dump_register!(cursor, RegNum::X7, "x7");
+            dump_register!(cursor, RegNum::X8, "x8");
+            dump_register!(cursor, RegNum::X9, "x9");
+            dump_register!(cursor, RegNum::X10, "x10");
+            dump_register!(cursor, RegNum::X11, "x11");
+            dump_register!(cursor, RegNum::X12, "x12");
+            dump_register!(cursor, RegNum::X13, "x13");
+            dump_register!(cursor, RegNum::X14, "x14");
+            dump_register!(cursor, RegNum::X15, "x15");
+            dump_register!(cursor, RegNum::X16, "x16");
+            dump_register!(cursor, RegNum::X17, "x17");
+            dump_register!(cursor, RegNum::X18, "x18");
+            dump_register!(cursor, RegNum::X19, "x19");
+            dump_register!(cursor, RegNum::X20, "x20");
+            dump_register!(cursor, RegNum::X21, "x21");
+            dump_register!(cursor, RegNum::X22, "x22");
+            dump_register!(cursor, RegNum::X23, "x23");
+            dump_register!(cursor, RegNum::X24, "x24");
+            dump_register!(cursor, RegNum::X25, "x25");
+            dump_register!(cursor, RegNum::X26, "x26");
+            dump_register!(cursor, RegNum::X27, "x27");
+            dump_register!(cursor, RegNum::X28, "x28");
+            dump_register!(cursor, RegNum::X29, "x29");
+            dump_register!(cursor, RegNum::X30, "x30");
+            dump_register!(cursor, RegNum::SP, "sp");
+            dump_register!(cursor, RegNum::PC, "pc");
+            dump_register!(cursor, RegNum::PSTATE, "pstate");
+            dump_register!(cursor, RegNum::CFA, "cfa");
+            println!();
+
+            let ip = cursor.register(RegNum::IP).unwrap();
+            assert_eq!(cursor.register(RegNum::PC).unwrap(), ip);
+            let sp = cursor.register(RegNum::SP).unwrap();
+            assert_ne!(sp, 0);
+            // libunwind defines the aarch64 CFA register as an alias of SP
+            assert_eq!(cursor.register(RegNum::CFA).unwrap(), sp);
+            frames.push(sp);
+
+            if !cursor.step().unwrap() {
+                break;
+            }
+        }
+
+        // the stack grows down, so each caller's frame is above its callee's
+        for w in frames.windows(2) {
+            assert!(w[1] >= w[0], "sp decreased: {:#x?}", frames);
+        }
+        // bar's frame holds the context, so its CFA, the stack pointer of foo, is above its own
+        assert!(frames[1] > frames[0]);
+    }
+
+    fn foo() {
+        bar();
+    }
+
+    foo();
+}
+
 #[test]
 #[cfg(feature = "ptrace")]
 fn remote() {
@@ -102,7 +195,7 @@ fn remote() {
     use std::ptr;
     use std::thread;
     use std::time::Duration;
-    use unwind::{Accessors, AddressSpace, Byteorder, PTraceState};
+    use unwind::PTraceState;
 
     let mut child = Command::new("sleep").arg("10").spawn().unwrap();
     thread::sleep(Duration::from_millis(10));
@@ -154,3 +247,227 @@ fn remote() {
     }
     child.kill().unwrap();
 }
+
+struct FixedRegisters {
+    ip: u64,
+    sp: u64,
+}
+
+impl CustomAccessors for FixedRegisters {
+    fn access_mem(&self, _: &AddressSpaceRef<Self>, _: u64) -> Result<u64> {
+        Err(Error::INVAL)
+    }
+
+    fn access_reg(&self, _: &AddressSpaceRef<Self>, reg: RegNum) -> Result<u64> {
+        match reg {
+            RegNum::IP => Ok(self.ip),
+            RegNum::SP => Ok(self.sp),
+            _ => Ok(0),
+        }
+    }
+}
+
+#[test]
+fn custom() {
+    let state = FixedRegisters {
+        ip: 0x1234,
+        sp: 0x5678,
+    };
+    let space = AddressSpace::new(&Accessors::custom(), Byteorder::DEFAULT).unwrap();
+    let mut cursor = Cursor::remote(&space, &state).unwrap();
+
+    assert_eq!(cursor.register(RegNum::IP).unwrap(), 0x1234);
+    assert_eq!(cursor.register(RegNum::SP).unwrap(), 0x5678);
+}
+
+struct Panicking;
+
+impl CustomAccessors for Panicking {
+    fn access_mem(&self, _: &AddressSpaceRef<Self>, _: u64) -> Result<u64> {
+        panic!("boom")
+    }
+
+    fn access_reg(&self, _: &AddressSpaceRef<Self>, _: RegNum) -> Result<u64> {
+        panic!("boom")
+    }
+}
+
+#[test]
+#[should_panic(expected = "boom")]
+fn custom_panic() {
+    let space = AddressSpace::new(&Accessors::custom(), Byteorder::DEFAULT).unwrap();
+    let mut cursor = Cursor::remote(&space, &Panicking).unwrap();
+    let _ = cursor.register(RegNum::IP);
+}
+
+#[test]
+#[cfg(not(pre16))]
+#[cfg(target_arch = "x86_64")]
+fn snapshot() {
+    use std::slice;
+    use unwind::{MapsEntry, Snapshot};
+
+    fn ips(mut cursor: Cursor<'_>) -> Vec<u64> {
+        let mut ips = vec![];
+        loop {
+            ips.push(cursor.register(RegNum::IP).unwrap());
+            match cursor.step() {
+                Ok(true) => {}
+                _ => return ips,
+            }
+        }
+    }
+
+    get_context!(context);
+    let mut cursor = Cursor::local(context).unwrap();
+
+    let mut snapshot = Snapshot::new();
+    for &reg in &[
+        RegNum::RAX,
+        RegNum::RDX,
+        RegNum::RCX,
+        RegNum::RBX,
+        RegNum::RSI,
+        RegNum::RDI,
+        RegNum::RBP,
+        RegNum::RSP,
+        RegNum::R8,
+        RegNum::R9,
+        RegNum::R10,
+        RegNum::R11,
+        RegNum::R12,
+        RegNum::R13,
+        RegNum::R14,
+        RegNum::R15,
+        RegNum::RIP,
+    ] {
+        snapshot.register(reg, cursor.register(reg).unwrap());
+    }
+
+    // copy up to 64KB of the stack above the stack pointer, staying within its mapping
+    let sp = cursor.register(RegNum::SP).unwrap();
+    let end = MapsEntry::read(std::process::id())
+        .unwrap()
+        .into_iter()
+        .find(|m| m.start() <= sp && sp < m.end())
+        .unwrap()
+        .end();
+    let len = (end - sp).min(64 * 1024) as usize;
+    let stack = unsafe { slice::from_raw_parts(sp as *const u8, len).to_vec() };
+    snapshot
+        .memory(sp, stack)
+        .mappings(std::process::id())
+        .unwrap();
+
+    let local = ips(cursor);
+
+    let space = AddressSpace::new(&Accessors::custom(), Byteorder::DEFAULT).unwrap();
+    let remote = ips(Cursor::remote(&space, &snapshot).unwrap());
+
+    assert!(remote.len() > 1);
+    assert_eq!(remote, local[..remote.len()]);
+}
+
+#[test]
+fn custom_set_register() {
+    use std::cell::Cell;
+
+    struct WritableIp(Cell<u64>);
+
+    impl CustomAccessors for WritableIp {
+        fn access_mem(&self, _: &AddressSpaceRef<Self>, _: u64) -> Result<u64> {
+            Err(Error::INVAL)
+        }
+
+        fn access_reg(&self, _: &AddressSpaceRef<Self>, reg: RegNum) -> Result<u64> {
+            match reg {
+                RegNum::IP => Ok(self.0.get()),
+                _ => Ok(0),
+            }
+        }
+
+        fn write_reg(&self, _: &AddressSpaceRef<Self>, reg: RegNum, value: u64) -> Result<()> {
+            match reg {
+                RegNum::IP => {
+                    self.0.set(value);
+                    Ok(())
+                }
+                _ => Err(Error::READONLYREG),
+            }
+        }
+    }
+
+    let state = WritableIp(Cell::new(0x1234));
+    let space = AddressSpace::new(&Accessors::custom(), Byteorder::DEFAULT).unwrap();
+    let mut cursor = Cursor::remote(&space, &state).unwrap();
+
+    cursor.set_register(RegNum::IP, 0x5678).unwrap();
+    assert_eq!(state.0.get(), 0x5678);
+    assert_eq!(cursor.register(RegNum::IP).unwrap(), 0x5678);
+}
+
+#[test]
+#[cfg(target_arch = "x86_64")]
+fn save_location() {
+    use unwind::SaveLocation;
+
+    #[inline(never)]
+    fn bar() {
+        get_context!(context);
+        let mut cursor = Cursor::local(context).unwrap();
+        assert!(cursor.step().unwrap());
+
+        // the return address of the previous frame is saved on the stack
+        match cursor.save_location(RegNum::IP).unwrap() {
+            SaveLocation::Memory(addr) => {
+                let ip = unsafe { *(addr as *const u64) };
+                assert_eq!(ip, cursor.register(RegNum::IP).unwrap());
+            }
+            loc => panic!("unexpected save location {:?}", loc),
+        }
+    }
+
+    bar();
+}
+
+#[test]
+#[cfg(target_arch = "x86_64")]
+fn landing_pad() {
+    struct Guard;
+
+    impl Drop for Guard {
+        fn drop(&mut self) {}
+    }
+
+    #[inline(never)]
+    fn bar() {
+        let _guard = Guard;
+        get_context!(context);
+        let mut cursor = Cursor::local(context).unwrap();
+
+        let info = cursor.procedure_info().unwrap();
+        // the guard's drop requires a landing pad and the Rust personality routine
+        assert_ne!(info.handler(), 0);
+        assert_ne!(info.lsda(), 0);
+    }
+
+    bar();
+}
+
+#[test]
+fn fp_registers() {
+    assert!(!RegNum::IP.is_fp_register());
+    assert!(!RegNum::SP.is_fp_register());
+
+    #[cfg(target_arch = "aarch64")]
+    {
+        assert!(RegNum::V0.is_fp_register());
+        assert!(RegNum::V31.is_fp_register());
+
+        get_context!(context);
+        let mut cursor = Cursor::local(context).unwrap();
+        assert_eq!(RegNum::FP_REGISTER_SIZE, 16);
+        assert_eq!(cursor.fp_register(RegNum::V8).unwrap().len(), 16);
+        assert!(cursor.fp_register(RegNum::X0).is_err());
+    }
+}